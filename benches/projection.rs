@@ -0,0 +1,78 @@
+//! Parse cost of a full 200 item search page against the `ItemIdPrice` projection
+//!
+//! ```text
+//! cargo bench --bench projection
+//! ```
+
+use std::hint::black_box;
+use std::time::{ Duration, Instant };
+use ebay_api_test::models::{ ItemIdPrice, SearchPage, SearchResponse };
+use serde_json::json;
+
+const ITEMS: usize = 200;
+const ROUNDS: u32 = 200;
+
+// A page shaped like a real one, with the fields eBay fills in for most listings
+fn page() -> Vec<u8> {
+    let items: Vec<serde_json::Value> = (0..ITEMS)
+        .map(|index| {
+            json!({
+                "itemId": format!("v1|{}|0", 110551991000u64 + index as u64),
+                "title": format!("Lenovo ThinkPad X1 Carbon Gen {} 14\" i7 16GB 512GB SSD", index % 11),
+                "price": { "value": format!("{}.99", 300 + index), "currency": "USD" },
+                "condition": "Used",
+                "conditionId": "3000",
+                "itemWebUrl": format!("https://www.ebay.com/itm/{}", 110551991000u64 + index as u64),
+                "itemHref": format!("https://api.ebay.com/buy/browse/v1/item/v1%7C{}%7C0", index),
+                "image": { "imageUrl": format!("https://i.ebayimg.com/images/g/{}/s-l225.jpg", index) },
+                "thumbnailImages": [
+                    { "imageUrl": format!("https://i.ebayimg.com/images/g/{}/s-l1600.jpg", index), "width": 1600, "height": 1200 }
+                ],
+                "shippingOptions": [
+                    { "shippingCost": { "value": "12.50", "currency": "USD" }, "shippingCostType": "FIXED" }
+                ],
+                "buyingOptions": ["FIXED_PRICE", "BEST_OFFER"],
+                "itemCreationDate": "2024-03-01T17:04:12.000Z",
+                "topRatedBuyingExperience": index % 3 == 0,
+                "marketingPrice": {
+                    "originalPrice": { "value": format!("{}.99", 400 + index), "currency": "USD" },
+                    "discountPercentage": "25",
+                    "priceTreatment": "LIST_PRICE"
+                }
+            })
+        })
+        .collect();
+
+    json!({ "total": 48213, "limit": ITEMS, "offset": 0, "itemSummaries": items })
+        .to_string()
+        .into_bytes()
+}
+
+fn time(rounds: u32, mut parse: impl FnMut()) -> Duration {
+    // One untimed round so both parsers start warm
+    parse();
+
+    let started = Instant::now();
+    for _ in 0..rounds {
+        parse();
+    }
+    started.elapsed() / rounds
+}
+
+fn main() {
+    let body = page();
+
+    let full = time(ROUNDS, || {
+        let response: SearchResponse = serde_json::from_slice(black_box(&body)).unwrap();
+        black_box(response);
+    });
+    let projected = time(ROUNDS, || {
+        let page: SearchPage<ItemIdPrice> = serde_json::from_slice(black_box(&body)).unwrap();
+        black_box(page);
+    });
+
+    println!("{} items, {} KiB per page, mean of {} rounds", ITEMS, body.len() / 1024, ROUNDS);
+    println!("SearchResponse:           {:>10.1?}", full);
+    println!("SearchPage<ItemIdPrice>:  {:>10.1?}", projected);
+    println!("projection takes {:.0}% of the full parse time", 100.0 * projected.as_secs_f64() / full.as_secs_f64());
+}
@@ -0,0 +1,53 @@
+//! # Paginated Search Example
+//!
+//! Runs a filtered, sorted search through `EbayClient`, follows the pages until enough
+//! listings are collected and prints them as a table.
+//!
+//! ```text
+//! EBAY_ACCESS_TOKEN=... cargo run --example paginated_search -- "thinkpad x1"
+//! ```
+//!
+//! Credentials come from the same environment variables as `EbayClient::from_env`:
+//! `EBAY_ACCESS_TOKEN`, or `EBAY_APP_ID` and `EBAY_CERT_ID`, plus `EBAY_ENV`.
+
+use ebay_api_test::client::EbayClient;
+use ebay_api_test::error::EbayError;
+use ebay_api_test::filter::{ BuyingOption, Condition, SearchFilter, SortOrder };
+
+// Listings to collect across pages
+const MAX_ITEMS: usize = 60;
+
+#[tokio::main]
+async fn main() -> Result<(), EbayError> {
+    let query = std::env::args().nth(1).unwrap_or_else(|| String::from("laptop"));
+
+    let client = EbayClient::from_env()?;
+
+    // Used or refurbished listings between 100 and 500, buy it now only, cheapest first
+    let filter = SearchFilter::new()
+        .price_range(Some(100.0), Some(500.0))
+        .conditions(&[Condition::Used, Condition::SellerRefurbished])
+        .buying_options(&[BuyingOption::FixedPrice]);
+
+    let config = client
+        .search_config().await?
+        .query(query.as_str())
+        .limit(20)
+        .filter(filter)
+        .sort(SortOrder::PriceAscending)
+        .build()?;
+
+    let items = client.search_all_with_progress(config, MAX_ITEMS, |collected, total| {
+        eprintln!("collected {} of {} matching listings", collected, total);
+    }).await?;
+
+    println!("{:<14} TITLE", "PRICE");
+    for item in &items {
+        match &item.price {
+            Some(price) => println!("{:<14} {}", price.to_string(), item.title),
+            None => println!("{:<14} {}", "in cart", item.title),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! # Blocking Module
+//!
+//! The `blocking` module, enabled by the `blocking` feature, provides `BlockingClient` with
+//! synchronous versions of `search_all` and `get_items` built on `reqwest::blocking`, for
+//! scripts that don't run an async runtime. Paging, retries and error handling are the
+//! ones `EbayClient` uses.
+//!
+//! These functions must not be called from within an async runtime such as a
+//! `#[tokio::main]` function: `reqwest::blocking` starts a runtime of its own and panics
+//! when it finds one already running. Use `EbayClient` there instead.
+//!
+//! Tokens are not refreshed: the access token in the config, or the one passed in, is
+//! used for every request. Request interceptors don't apply either.
+//!
+//! ## Example Usage
+//!
+//! ```no_run
+//! use ebay_api_test::blocking::BlockingClient;
+//! use ebay_api_test::ebay_api::SearchConfig;
+//!
+//! fn main() {
+//!     # let access_token = String::from("Your-OAuth-Access-Token");
+//!     let client = BlockingClient::new();
+//!     let config = SearchConfig::new(serde_json::json!("laptop"), access_token);
+//!
+//!     let items = client.search_all(config, 100);
+//! }
+//! ```
+
+use reqwest::header::HeaderMap;
+use serde_json::json;
+
+use crate::client::{ Pager, RetryConfig, DEFAULT_PAGE_SIZE };
+use crate::ebay_api::{ check_response, default_headers, Environment, SearchConfig, ITEM_PATH, SEARCH_PATH };
+use crate::error::{ EbayError, DEFAULT_MAX_ERROR_BODY };
+use crate::item::{ ItemsResponse, MAX_ITEM_IDS_PER_REQUEST };
+use crate::models::{ Item, ItemSummary, SearchResponse };
+
+#[derive(Debug, Default)]
+/// Synchronous client for scripts without an async runtime
+pub struct BlockingClient {
+    pub http: reqwest::blocking::Client,
+    pub environment: Environment,
+    pub retry: RetryConfig,
+    /// Overrides the environment's host for every endpoint when set
+    pub base_url: Option<String>,
+}
+
+impl BlockingClient {
+    /// Create a new client targeting the sandbox
+    pub fn new() -> Self {
+        BlockingClient::default()
+    }
+
+    /// Create a client that sends every request to another host, such as a local fixture
+    /// server, e.g. `with_base_url("http://localhost:8080")`
+    pub fn with_base_url(base_url: &str) -> Self {
+        BlockingClient {
+            base_url: Some(base_url.trim_end_matches('/').to_string()),
+            ..BlockingClient::default()
+        }
+    }
+
+    /// Target sandbox or production for item lookups. Searches go to their config's URL.
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Replace the limits `search_all` applies to retries and total requests
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Blocking version of `EbayClient::search_all`: fetch every page of the search until
+    /// `max_items` results have been collected or eBay runs out of results, retrying
+    /// transient and connection errors with the same backoff
+    pub fn search_all(
+        &self,
+        mut config: SearchConfig,
+        max_items: usize
+    ) -> Result<Vec<ItemSummary>, EbayError> {
+        if self.base_url.is_some() {
+            config.search_url = self.endpoint(SEARCH_PATH);
+        }
+
+        // Same page size as EbayClient when the config sets none
+        if !config.search_parameters.contains_key("limit") {
+            config.search_parameters.insert(String::from("limit"), json!(DEFAULT_PAGE_SIZE));
+        }
+
+        let mut pager = Pager::new(&config, max_items, &self.retry, DEFAULT_PAGE_SIZE);
+
+        while let Some(offset) = pager.next_offset()? {
+            config.search_parameters.insert(String::from("offset"), json!(offset));
+
+            match self.search_page(&config) {
+                Ok(response) => pager.page(response),
+                Err(err) => std::thread::sleep(pager.failed(err)?),
+            }
+        }
+
+        Ok(pager.items)
+    }
+
+    /// Blocking version of `EbayClient::get_items`: full details for many listings, 20 IDs
+    /// per request. Batches are sent one after another.
+    pub fn get_items(&self, ids: &[String], access_token: &str) -> Result<Vec<Item>, EbayError> {
+        let url = format!("{}/", self.endpoint(ITEM_PATH));
+        let headers = default_headers(access_token);
+
+        let mut items = Vec::new();
+        for batch in ids.chunks(MAX_ITEM_IDS_PER_REQUEST) {
+            let parameters = [("item_ids", batch.join(","))];
+            let body = self.send(&url, &headers, &parameters)?;
+            let response: ItemsResponse = serde_json::from_slice(&body)?;
+
+            items.extend(response.items);
+        }
+
+        Ok(items)
+    }
+
+    // Full URL of an API path on the base URL, or on the environment's host without one
+    fn endpoint(&self, path: &str) -> String {
+        match &self.base_url {
+            Some(base_url) => format!("{}{}", base_url, path),
+            None => format!("{}{}", self.environment.base_url(), path),
+        }
+    }
+
+    fn search_page(&self, config: &SearchConfig) -> Result<SearchResponse, EbayError> {
+        let body = self.send(&config.search_url, &config.headers, &config.search_parameters)?;
+
+        serde_json::from_slice(&body).map_err(EbayError::Json)
+    }
+
+    fn send<P: serde::Serialize + ?Sized>(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        parameters: &P
+    ) -> Result<Vec<u8>, EbayError> {
+        let response = self.http
+            .get(url)
+            .headers(headers.clone())
+            .query(parameters)
+            .send()?;
+
+        let status = response.status();
+        let response_headers = response.headers().clone();
+        let body = response.bytes()?.to_vec();
+
+        check_response(status, &response_headers, &body)
+            .map_err(|err| err.truncate_body(DEFAULT_MAX_ERROR_BODY))?;
+
+        Ok(body)
+    }
+}
@@ -0,0 +1,147 @@
+//! # Cache Module
+//!
+//! The `cache` module provides `Cache`, an in-memory LRU cache of search responses with a
+//! time to live. Attaching one to an `EbayClient` makes repeated identical searches return
+//! the stored response instead of spending another request against eBay's rate limit.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use ebay_api_test::cache::Cache;
+//! use ebay_api_test::client::EbayClient;
+//!
+//! // Keep up to 100 distinct searches for five minutes each
+//! let client = EbayClient::new().with_cache(Cache::new(100, Duration::from_secs(300)));
+//! ```
+
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{ Duration, Instant };
+use lru::LruCache;
+use reqwest::header::ACCEPT_LANGUAGE;
+
+use crate::context::END_USER_CONTEXT_HEADER;
+use crate::ebay_api::{ query_string, SearchConfig, MARKETPLACE_HEADER };
+use crate::models::SearchResponse;
+
+#[derive(Debug)]
+struct CacheEntry {
+    response: SearchResponse,
+    stored_at: Instant,
+}
+
+#[derive(Debug)]
+/// Least recently used cache of search responses, each valid for `ttl`
+pub struct Cache {
+    entries: Mutex<LruCache<String, CacheEntry>>,
+    ttl: Duration,
+}
+
+impl Cache {
+    /// Create a new Cache
+    /// capacity -> most responses kept at once, at least one
+    /// ttl -> how long a stored response stays valid
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+
+        Cache {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Key identifying a search: endpoint, every query parameter and the headers that change
+    /// what eBay answers, namely the marketplace, the language (`Accept-Language`) and the
+    /// buyer's context. Parameters are rendered as a sorted query string, so equal searches
+    /// always produce equal keys.
+    pub fn key(config: &SearchConfig) -> String {
+        let header = |name: &str| {
+            config.headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+        };
+        let parameters = query_string(&config.search_parameters);
+
+        format!(
+            "{} {} {} {} {}",
+            config.search_url,
+            header(MARKETPLACE_HEADER),
+            header(ACCEPT_LANGUAGE.as_str()),
+            header(END_USER_CONTEXT_HEADER),
+            parameters
+        )
+    }
+
+    /// Stored response for the key, unless it is missing or older than the ttl
+    pub fn get(&self, key: &str) -> Option<SearchResponse> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => Some(entry.response.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a response, evicting the least recently used one if the cache is full
+    pub fn insert(&self, key: String, response: SearchResponse) {
+        let entry = CacheEntry {
+            response,
+            stored_at: Instant::now(),
+        };
+
+        self.entries.lock().unwrap().put(key, entry);
+    }
+
+    /// Drop every stored response
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::Marketplace;
+
+    fn config(locale: &str, marketplace: Marketplace) -> SearchConfig {
+        SearchConfig::builder()
+            .query("laptop")
+            .marketplace(marketplace)
+            .locale(locale)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn key_depends_on_the_language() {
+        let german = config("de-DE", Marketplace::Germany);
+        let english = config("en-GB", Marketplace::Germany);
+
+        assert_ne!(Cache::key(&german), Cache::key(&english));
+        assert_eq!(Cache::key(&german), Cache::key(&german.clone()));
+    }
+
+    #[test]
+    fn key_depends_on_the_marketplace() {
+        let germany = config("de-DE", Marketplace::Germany);
+        let austria = config("de-DE", Marketplace::Austria);
+
+        assert_ne!(Cache::key(&germany), Cache::key(&austria));
+    }
+
+    #[test]
+    fn localized_responses_are_cached_apart() {
+        let cache = Cache::new(10, Duration::from_secs(60));
+        let german = SearchResponse { total: 1, ..SearchResponse::default() };
+        cache.insert(Cache::key(&config("de-DE", Marketplace::Germany)), german);
+
+        assert!(cache.get(&Cache::key(&config("en-GB", Marketplace::Germany))).is_none());
+        assert_eq!(cache.get(&Cache::key(&config("de-DE", Marketplace::Germany))).unwrap().total, 1);
+    }
+}
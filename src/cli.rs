@@ -0,0 +1,85 @@
+//! # CLI Module
+//!
+//! The `cli` module defines the command line arguments of the `ebay-api-test` binary.
+
+use std::path::PathBuf;
+use clap::{ Args, Parser, ValueEnum };
+
+use ebay_api_test::ebay_api::Environment;
+use ebay_api_test::models::ClientSort;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Search eBay listings from the terminal")]
+pub struct Cli {
+    /// What to search for
+    #[arg(default_value = "laptop")]
+    pub query: String,
+
+    #[command(flatten)]
+    pub environment: EnvironmentFlags,
+
+    /// Write the results to this file instead of stdout, creating parent directories
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Print the results as compact JSON on one line instead of pretty printed
+    #[arg(long)]
+    pub compact: bool,
+
+    /// Sort the results after fetching them so the output is the same on every run.
+    /// This is separate from eBay's own sort order.
+    #[arg(long, value_enum, value_name = "FIELD")]
+    pub sort_client: Option<SortClient>,
+
+    /// Print only the number of matching listings and exit, without fetching them
+    #[arg(long, conflicts_with_all = ["output", "compact", "sort_client"])]
+    pub count_only: bool,
+
+    /// Log only errors instead of warnings too. Logs always go to stderr, so stdout carries
+    /// nothing but the results either way.
+    #[arg(long, short)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+/// Fields `--sort-client` can sort by
+pub enum SortClient {
+    /// Cheapest first, then by item ID
+    Price,
+    /// By item ID
+    ItemId,
+}
+
+impl SortClient {
+    /// The matching library sort order
+    pub fn client_sort(&self) -> ClientSort {
+        match self {
+            SortClient::Price => ClientSort::Price,
+            SortClient::ItemId => ClientSort::ItemId,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[group(multiple = false)]
+/// Which eBay environment to talk to; the flags are mutually exclusive
+pub struct EnvironmentFlags {
+    /// Send requests to the eBay sandbox (the default)
+    #[arg(long)]
+    pub sandbox: bool,
+
+    /// Send requests to production eBay
+    #[arg(long)]
+    pub production: bool,
+}
+
+impl EnvironmentFlags {
+    /// The selected environment, the sandbox unless `--production` was passed
+    pub fn environment(&self) -> Environment {
+        if self.production {
+            Environment::Production
+        } else {
+            Environment::Sandbox
+        }
+    }
+}
@@ -0,0 +1,941 @@
+//! # Client Module
+//!
+//! The `client` module provides `EbayClient`, which owns the HTTP connection pool and any
+//! state shared between requests, such as the response cache.
+//!
+//! ## Example Usage
+//!
+//! ```no_run
+//! use ebay_api_test::client::EbayClient;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), ebay_api_test::error::EbayError> {
+//!     // Reads EBAY_ACCESS_TOKEN, or EBAY_APP_ID and EBAY_CERT_ID, plus EBAY_ENV
+//!     let client = EbayClient::from_env()?;
+//!     let response = client.search_query("laptop").await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! A config can also be built by hand and passed to `search`:
+//!
+//! ```no_run
+//! use ebay_api_test::ebay_api::SearchConfig;
+//! use ebay_api_test::client::EbayClient;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     # let access_token = String::from("Your-OAuth-Access-Token");
+//!     let client = EbayClient::new();
+//!     let config = SearchConfig::new(serde_json::json!("laptop"), access_token);
+//!
+//!     let response = client.search(config).await;
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{ Duration, Instant };
+use bytes::Bytes;
+use futures::future::join_all;
+use reqwest::header::{ self, HeaderMap };
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::Deserialize;
+use serde_json::{ json, Value };
+use tokio::sync::Semaphore;
+
+use crate::cache::Cache;
+use crate::ebay_api::{ default_headers, receive_bytes, send_get, send_get_bytes, send_get_response, Environment, SearchConfig, SearchConfigBuilder, MAX_LIMIT, SEARCH_PATH };
+use crate::error::{ EbayError, DEFAULT_MAX_ERROR_BODY };
+use crate::fieldgroups::FieldGroup;
+use crate::filter::SearchFilter;
+use crate::interceptor::{ NoopInterceptor, RequestInterceptor };
+use crate::marketplace::Marketplace;
+use crate::models::{ ApiWarning, ItemParseError, ItemSummary, SearchPage, SearchResponse, SearchResult };
+use crate::telemetry;
+use crate::token::{ expose, secret, Secret, TokenManager };
+
+/// eBay never returns results past offset + limit = 10,000
+pub const MAX_RESULT_WINDOW: u64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct WarningsOnly {
+    #[serde(default)]
+    warnings: Vec<ApiWarning>,
+}
+
+/// Requests a client lets run at the same time unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 5;
+
+/// Page size eBay uses when a search sets no `limit`
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+
+#[derive(Debug, Clone)]
+/// Limits on how hard `search_all` keeps trying when pages fail or never run out
+pub struct RetryConfig {
+    /// Transient errors in a row after which the search is abandoned
+    pub max_consecutive_errors: u32,
+    /// Delay before the first retry, doubled for every further one unless eBay
+    /// sent a `Retry-After`
+    pub base_delay: Duration,
+    /// Requests allowed beyond the number of pages `max_items` needs
+    pub request_margin: u32,
+    /// Also retry requests that failed to connect or timed out, with the same backoff
+    pub retry_connection_errors: bool,
+    /// Longest the doubling backoff waits before a retry. A `Retry-After` from eBay is
+    /// still honored as sent.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_consecutive_errors: 3,
+            base_delay: Duration::from_millis(500),
+            request_margin: 5,
+            retry_connection_errors: true,
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// How long to wait before retry number `attempt`, counting from 1. Without a
+    /// `Retry-After` the delay doubles every attempt up to `max_backoff`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after.unwrap_or_else(|| {
+            self.base_delay
+                .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+                .min(self.max_backoff)
+        })
+    }
+}
+
+/// Progress of a `search_all`, deciding which offset to request next and whether a failed
+/// page is tried again, shared by the async and blocking clients
+pub(crate) struct Pager<'a> {
+    retry: &'a RetryConfig,
+    max_items: usize,
+    page_size: u64,
+    request_budget: u64,
+    offset: u64,
+    requests: u64,
+    pub(crate) consecutive_errors: u32,
+    finished: bool,
+    pub(crate) items: Vec<ItemSummary>,
+}
+
+impl<'a> Pager<'a> {
+    /// Paging for the config, which is sent with `default_limit` results per page when it
+    /// sets no limit of its own
+    pub(crate) fn new(
+        config: &SearchConfig,
+        max_items: usize,
+        retry: &'a RetryConfig,
+        default_limit: u64
+    ) -> Self {
+        let page_size = numeric_param(config, "limit").unwrap_or(default_limit).max(1);
+        let pages_needed = (max_items as u64).div_ceil(page_size);
+
+        Pager {
+            retry,
+            max_items,
+            page_size,
+            request_budget: pages_needed + u64::from(retry.request_margin),
+            offset: numeric_param(config, "offset").unwrap_or(0),
+            requests: 0,
+            consecutive_errors: 0,
+            finished: false,
+            items: Vec::new(),
+        }
+    }
+
+    /// Offset of the page to request next, None once the search is complete, or a
+    /// `Pagination` error when the request budget is spent
+    pub(crate) fn next_offset(&mut self) -> Result<Option<u64>, EbayError> {
+        if self.finished || self.items.len() >= self.max_items || self.offset >= MAX_RESULT_WINDOW {
+            return Ok(None);
+        }
+
+        if self.requests >= self.request_budget {
+            return Err(
+                EbayError::Pagination(
+                    format!(
+                        "sent {} requests, the most {} items at {} per page should need, without finishing",
+                        self.requests,
+                        self.max_items,
+                        self.page_size
+                    )
+                )
+            );
+        }
+        self.requests += 1;
+
+        Ok(Some(self.offset))
+    }
+
+    /// Collect a page and move past it
+    pub(crate) fn page(&mut self, response: SearchResponse) {
+        self.consecutive_errors = 0;
+
+        let page_len = response.item_summaries.len() as u64;
+        self.finished = page_len == 0 ||
+            self.offset + page_len >= response.total ||
+            response.next.is_none();
+
+        self.items.extend(response.item_summaries);
+        self.items.truncate(self.max_items);
+        self.offset += page_len;
+    }
+
+    /// How long to wait before trying the failed page again, or the error to stop with when
+    /// it isn't retried
+    pub(crate) fn failed(&mut self, err: EbayError) -> Result<Duration, EbayError> {
+        let retryable = err.is_transient() || (self.retry.retry_connection_errors && err.is_connection_error());
+        if !retryable {
+            return Err(err);
+        }
+
+        self.consecutive_errors += 1;
+        if self.consecutive_errors >= self.retry.max_consecutive_errors {
+            return Err(
+                EbayError::Pagination(
+                    format!(
+                        "{} consecutive errors at offset {}, last: {}",
+                        self.consecutive_errors,
+                        self.offset,
+                        err
+                    )
+                )
+            );
+        }
+
+        let delay = self.retry.delay(self.consecutive_errors, err.retry_after());
+        tracing::warn!(error = %err, delay_ms = delay.as_millis() as u64, "retrying page");
+        telemetry::record_retry(if err.is_connection_error() { "connection" } else { "transient" });
+
+        Ok(delay)
+    }
+}
+
+#[derive(Debug)]
+/// Client used to send requests to eBay
+pub struct EbayClient {
+    pub http: reqwest::Client,
+    pub cache: Option<Cache>,
+    pub environment: Environment,
+    pub access_token: Option<Secret>,
+    pub token_manager: Option<Arc<TokenManager>>,
+    pub strict: bool,
+    pub retry: RetryConfig,
+    /// Overrides the environment's host for every endpoint when set
+    pub base_url: Option<String>,
+    /// Permits shared by every request this client sends, bounding how many are in flight
+    pub limiter: Arc<Semaphore>,
+    /// Hook applied to every request just before it is sent
+    pub interceptor: Arc<dyn RequestInterceptor>,
+    /// Page size sent for searches whose config sets no `limit` of its own
+    pub default_limit: u32,
+    /// Bytes of an error response body kept in `EbayError::Api`
+    pub max_error_body: usize,
+    // Set when `http` was handed in by the caller, whose settings `compression` must not replace
+    shared_http: bool,
+}
+
+impl Default for EbayClient {
+    fn default() -> Self {
+        EbayClient {
+            http: reqwest::Client::default(),
+            cache: None,
+            environment: Environment::default(),
+            access_token: None,
+            token_manager: None,
+            strict: false,
+            retry: RetryConfig::default(),
+            base_url: None,
+            limiter: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            interceptor: Arc::new(NoopInterceptor),
+            default_limit: DEFAULT_PAGE_SIZE as u32,
+            max_error_body: DEFAULT_MAX_ERROR_BODY,
+            shared_http: false,
+        }
+    }
+}
+
+impl EbayClient {
+    /// Create a new client with no cache
+    pub fn new() -> Self {
+        EbayClient::default()
+    }
+
+    /// Start from the defaults and chain the settings that differ, e.g.
+    /// `EbayClient::builder().default_limit(100)?.strict(true)`. The client is configured
+    /// through its own setters, so this is the same as `new`.
+    pub fn builder() -> Self {
+        EbayClient::default()
+    }
+
+    /// Allow at most this many requests in flight at once across every call on this
+    /// client, e.g. `search_many` and `get_items`, to stay under eBay's rate limits
+    pub fn max_concurrent_requests(mut self, permits: usize) -> Self {
+        self.limiter = Arc::new(Semaphore::new(permits.max(1)));
+        self
+    }
+
+    /// In strict mode a successful response carrying `warnings` (a dropped filter, an
+    /// auto-corrected keyword, ...) is returned as a `Warnings` error instead
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Run every request through the interceptor before sending it, e.g. to add the
+    /// signature a custom gateway requires
+    pub fn interceptor(mut self, interceptor: Arc<dyn RequestInterceptor>) -> Self {
+        self.interceptor = interceptor;
+        self
+    }
+
+    /// Page size sent for every search whose config sets no `limit`, `DEFAULT_PAGE_SIZE`
+    /// (eBay's own default, 50) unless changed. A config can still override it with
+    /// `.limit(n)`. A `Validation` error when the limit is 0 or above `MAX_LIMIT`.
+    pub fn default_limit(mut self, limit: u32) -> Result<Self, EbayError> {
+        if limit == 0 || u64::from(limit) > MAX_LIMIT {
+            return Err(
+                EbayError::Validation(format!("default limit {} must be between 1 and {}", limit, MAX_LIMIT))
+            );
+        }
+
+        self.default_limit = limit;
+        Ok(self)
+    }
+
+    /// Keep at most this many bytes of an error response's body in `EbayError::Api`, 2 KB
+    /// unless changed. eBay's `errors` are parsed from the whole body before it is cut.
+    pub fn max_error_body(mut self, max_len: usize) -> Self {
+        self.max_error_body = max_len;
+        self
+    }
+
+    /// Replace the limits `search_all` applies to retries and total requests
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Create a client from environment variables:
+    ///
+    /// - `EBAY_ACCESS_TOKEN`: OAuth access token, used as is when set
+    /// - `EBAY_APP_ID` and `EBAY_CERT_ID`: developer keyset, used to fetch tokens when no
+    ///   access token is set
+    /// - `EBAY_ENV`: `sandbox` (default) or `production`
+    /// - `EBAY_BASE_URL`: optional server to send every request to instead of eBay
+    ///
+    /// Returns a `Config` error naming the first missing variable.
+    pub fn from_env() -> Result<Self, EbayError> {
+        let environment = match std::env::var("EBAY_ENV") {
+            Ok(value) => value.parse()?,
+            Err(_) => Environment::Sandbox,
+        };
+
+        let mut client = match env_var("EBAY_BASE_URL") {
+            Some(base_url) => EbayClient::with_base_url(&base_url),
+            None => EbayClient::new(),
+        };
+        client.environment = environment;
+
+        if let Some(access_token) = env_var("EBAY_ACCESS_TOKEN") {
+            client.access_token = Some(secret(access_token));
+            return Ok(client);
+        }
+
+        let app_id = env_var("EBAY_APP_ID").ok_or_else(|| {
+            EbayError::Config(String::from("EBAY_APP_ID is not set (and neither is EBAY_ACCESS_TOKEN)"))
+        })?;
+        let cert_id = env_var("EBAY_CERT_ID").ok_or_else(|| {
+            EbayError::Config(String::from("EBAY_CERT_ID is not set"))
+        })?;
+
+        let mut manager = TokenManager::new(&app_id, &cert_id).environment(environment);
+        if let Some(base_url) = &client.base_url {
+            manager = manager.base_url(base_url);
+        }
+        client.token_manager = Some(Arc::new(manager));
+
+        Ok(client)
+    }
+
+    /// Access token for the next request: the fixed token when one was given,
+    /// otherwise the Token Manager's cached token, fetched on first use
+    pub async fn access_token(&self) -> Result<String, EbayError> {
+        if let Some(access_token) = &self.access_token {
+            return Ok(expose(access_token).to_string());
+        }
+
+        match &self.token_manager {
+            Some(manager) => manager.access_token().await,
+            None => Err(EbayError::Config(String::from("client has no access token or token manager"))),
+        }
+    }
+
+    /// Builder pre-populated with this client's environment and credentials. The app and
+    /// cert IDs come from the client's Token Manager and stay empty without one.
+    pub async fn search_config(&self) -> Result<SearchConfigBuilder, EbayError> {
+        let mut builder = SearchConfig::builder()
+            .access_token(self.access_token().await?)
+            .environment(self.environment);
+
+        if let Some(base_url) = &self.base_url {
+            builder = builder.search_url(&format!("{}{}", base_url, SEARCH_PATH));
+        }
+
+        if let Some(manager) = &self.token_manager {
+            builder = builder
+                .app_id(manager.app_id())
+                .cert_id(manager.cert_id())
+                .token_manager(manager.clone());
+        }
+
+        Ok(builder)
+    }
+
+    /// Search for the query with the default settings and this client's credentials
+    pub async fn search_query(&self, query: impl Into<Value>) -> Result<SearchResponse, EbayError> {
+        let config = self.search_config().await?.query(query).build()?;
+
+        self.search(config).await
+    }
+
+    /// Create a client that sends every request (search, item lookups) to another server,
+    /// such as a local fixture server: `EbayClient::with_base_url("http://localhost:8080")`.
+    /// Searches go there whatever `search_url` their config was built with. `from_env`
+    /// reads the same setting from `EBAY_BASE_URL` and applies it to token requests as well.
+    pub fn with_base_url(base_url: &str) -> Self {
+        EbayClient {
+            base_url: Some(base_url.trim_end_matches('/').to_string()),
+            ..EbayClient::default()
+        }
+    }
+
+    /// Create a client that sends requests through an existing `reqwest::Client`, sharing
+    /// its connection pool and its TLS, proxy and timeout settings with the rest of the app
+    pub fn with_http_client(http: reqwest::Client) -> Self {
+        EbayClient {
+            http,
+            shared_http: true,
+            ..EbayClient::default()
+        }
+    }
+
+    /// Ask eBay for gzip or brotli compressed responses (the default) and decompress them
+    /// transparently, or turn compression off to receive plain bodies. The parsed results
+    /// are the same either way; compression only saves bandwidth on large pages.
+    ///
+    /// This rebuilds the client's own HTTP client, so it fails with a `Config` error on a
+    /// client from `with_http_client` rather than dropping that client's settings; set
+    /// `gzip` and `brotli` on its `reqwest::ClientBuilder` instead.
+    pub fn compression(mut self, enabled: bool) -> Result<Self, EbayError> {
+        if self.shared_http {
+            return Err(
+                EbayError::Config(
+                    String::from("compression is set on the reqwest::Client given to with_http_client")
+                )
+            );
+        }
+
+        self.http = reqwest::Client::builder().gzip(enabled).brotli(enabled).build()?;
+        Ok(self)
+    }
+
+    /// Target sandbox or production
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Full URL of an endpoint path on the base URL, or on the environment's host
+    pub fn endpoint(&self, path: &str) -> String {
+        match &self.base_url {
+            Some(base_url) => format!("{}{}", base_url, path),
+            None => format!("{}{}", self.environment.base_url(), path),
+        }
+    }
+
+    /// Serve repeated identical searches from the cache while they are fresh
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Run the search described by the config.
+    /// If the client has a cache and it holds a fresh response for the same search, that
+    /// response is returned without contacting eBay. If eBay reports the access token as
+    /// expired and the config has a Token Manager, the token is refreshed and the request
+    /// is retried once.
+    pub async fn search(&self, mut config: SearchConfig) -> Result<SearchResponse, EbayError> {
+        self.search_page(&mut config, 0).await
+    }
+
+    /// Same as `search`, adding headers for this call only, e.g. a trace ID, without touching
+    /// the client. They are merged like `SearchConfig::with_headers`, so `Authorization` and
+    /// `Content-Type` can't be overridden. The cache only tells searches apart by their
+    /// marketplace, language and buyer context headers, so a fresh cached response for the
+    /// same search is still served when other headers differ.
+    pub async fn search_with_headers(
+        &self,
+        config: SearchConfig,
+        extra: &HeaderMap
+    ) -> Result<SearchResponse, EbayError> {
+        self.search(config.with_headers(extra.clone())).await
+    }
+
+    /// Same as `search`, returning the items together with their aspect facets. The
+    /// `ASPECT_REFINEMENTS` field group (and `MATCHING_ITEMS`, so the items still come back)
+    /// is added to the config's fieldgroups when missing; a `COMPACT` config can't carry
+    /// facets and is rejected with a `Validation` error.
+    pub async fn search_with_facets(&self, mut config: SearchConfig) -> Result<SearchResult, EbayError> {
+        let current = match config.search_parameters.get("fieldgroups") {
+            Some(Value::String(fieldgroups)) => fieldgroups.clone(),
+            _ => String::new(),
+        };
+        let mut groups: Vec<&str> = current
+            .split(',')
+            .map(str::trim)
+            .filter(|group| !group.is_empty())
+            .collect();
+
+        if groups.contains(&FieldGroup::Compact.as_str()) {
+            return Err(EbayError::Validation(String::from("fieldgroups: COMPACT results carry no facets")));
+        }
+
+        if !groups.contains(&FieldGroup::Full.as_str()) {
+            for group in [FieldGroup::MatchingItems, FieldGroup::AspectRefinements] {
+                if !groups.contains(&group.as_str()) {
+                    groups.push(group.as_str());
+                }
+            }
+        }
+        config.search_parameters.insert(String::from("fieldgroups"), json!(groups.join(",")));
+
+        Ok(SearchResult::from(self.search(config).await?))
+    }
+
+    /// Number of listings matching the query and filter, without fetching them: the search is
+    /// sent with `limit=1` and only eBay's `total` is kept
+    pub async fn count(
+        &self,
+        query: impl Into<Value>,
+        filter: SearchFilter,
+        access_token: &str
+    ) -> Result<u64, EbayError> {
+        let config = SearchConfig::builder()
+            .query(query)
+            .filter(filter)
+            .limit(1)
+            .access_token(access_token)
+            .environment(self.environment)
+            .build()?;
+
+        Ok(self.search(config).await?.total)
+    }
+
+    /// Run several searches concurrently, as many at a time as `max_concurrent_requests`
+    /// allows. Results are in the same order as the configs.
+    pub async fn search_many(
+        &self,
+        configs: Vec<SearchConfig>
+    ) -> Vec<Result<SearchResponse, EbayError>> {
+        join_all(configs.into_iter().map(|config| self.search(config))).await
+    }
+
+    /// Run the same query on several marketplaces concurrently, e.g. to compare prices
+    /// across regions. Each search carries its own marketplace header, and price ranges
+    /// default to its currency. Results are keyed by marketplace, each with its own outcome:
+    /// a token is not necessarily accepted on every marketplace, so one marketplace failing
+    /// (often with a 403) doesn't hide the others' results.
+    pub async fn search_across(
+        &self,
+        query: impl Into<Value>,
+        marketplaces: &[Marketplace],
+        access_token: &str
+    ) -> HashMap<Marketplace, Result<SearchResponse, EbayError>> {
+        let query = query.into();
+
+        let searches = marketplaces.iter().map(|marketplace| {
+            let config = SearchConfig::builder()
+                .query(query.clone())
+                .marketplace(*marketplace)
+                .access_token(access_token)
+                .environment(self.environment)
+                .build();
+
+            async move {
+                let result = match config {
+                    Ok(config) => self.search(config).await,
+                    Err(err) => Err(err),
+                };
+
+                (*marketplace, result)
+            }
+        });
+
+        join_all(searches).await.into_iter().collect()
+    }
+
+    /// Fetch every page of the search until `max_items` results have been collected
+    /// or eBay runs out of results. Pages are requested with the config's `limit`,
+    /// starting at its `offset`.
+    pub async fn search_all(
+        &self,
+        config: SearchConfig,
+        max_items: usize
+    ) -> Result<Vec<ItemSummary>, EbayError> {
+        self.search_all_with_progress(config, max_items, |_, _| {}).await
+    }
+
+    /// Same as `search_all`, calling `progress(fetched, total)` after each page with the
+    /// number of items collected so far and the total number of matches eBay reported.
+    ///
+    /// The callback runs on the task driving the search, between two requests, so it
+    /// must return quickly and must never block (no waiting on locks, channels or user
+    /// input). Hand anything slow off to another task, e.g. through an unbounded channel.
+    ///
+    /// Transient errors (rate limiting, server errors) are retried with a backoff, but the
+    /// search is abandoned with a `Pagination` error after `retry.max_consecutive_errors`
+    /// of them in a row, or once it has sent more requests than `max_items` needs pages
+    /// plus `retry.request_margin`, so a misbehaving server can't keep it running forever.
+    pub async fn search_all_with_progress<F>(
+        &self,
+        mut config: SearchConfig,
+        max_items: usize,
+        mut progress: F
+    ) -> Result<Vec<ItemSummary>, EbayError>
+    where
+        F: FnMut(usize, u64),
+    {
+        let mut pager = Pager::new(&config, max_items, &self.retry, u64::from(self.default_limit));
+
+        while let Some(offset) = pager.next_offset()? {
+            config.search_parameters.insert(String::from("offset"), json!(offset));
+
+            match self.search_page(&mut config, pager.consecutive_errors).await {
+                Ok(response) => {
+                    let total = response.total;
+                    pager.page(response);
+                    progress(pager.items.len(), total);
+                }
+                Err(err) => tokio::time::sleep(pager.failed(err)?).await,
+            }
+        }
+
+        Ok(pager.items)
+    }
+
+    // Run one search, refreshing the token in the config itself so later pages reuse it.
+    // `retries` is how many times this page has already been tried, for the request span.
+    pub(crate) async fn search_page(
+        &self,
+        config: &mut SearchConfig,
+        retries: u32
+    ) -> Result<SearchResponse, EbayError> {
+        self.retarget(config);
+
+        let cache_key = self.cache.as_ref().map(|_| Cache::key(config));
+
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            if let Some(response) = cache.get(key) {
+                tracing::debug!("serving search from cache");
+                return Ok(response);
+            }
+        }
+
+        let started = Instant::now();
+        let body = self.send_search_body(config, retries).await?;
+        let response = self.parse_search(Some(config), &body, started.elapsed())?;
+
+        if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+            cache.insert(key, response.clone());
+        }
+
+        Ok(response)
+    }
+
+    /// Same as `search`, also returning the exact bytes eBay sent. The cache is bypassed
+    /// so the bytes always come from eBay.
+    pub async fn search_raw(&self, mut config: SearchConfig) -> Result<(SearchResponse, Bytes), EbayError> {
+        self.retarget(&mut config);
+
+        let started = Instant::now();
+        let body = self.send_search_body(&mut config, 0).await?;
+        let response = self.parse_search(Some(&config), &body, started.elapsed())?;
+
+        Ok((response, body))
+    }
+
+    /// Run the search and parse the response into a type of your own, for fields this
+    /// crate doesn't model. The cache is bypassed; strict mode still applies.
+    pub async fn search_as<T: DeserializeOwned>(&self, mut config: SearchConfig) -> Result<T, EbayError> {
+        self.retarget(&mut config);
+
+        let body = self.send_search_body(&mut config, 0).await?;
+
+        if self.strict {
+            let parsed: WarningsOnly = serde_json::from_slice(&body)?;
+
+            if !parsed.warnings.is_empty() {
+                return Err(EbayError::Warnings(parsed.warnings));
+            }
+        }
+
+        serde_json::from_slice(&body).map_err(EbayError::Json)
+    }
+
+    /// Same as `search`, skipping item summaries that fail to parse instead of failing the
+    /// whole page; they come back with their index and error next to the response. The
+    /// cache is bypassed; strict mode still applies.
+    pub async fn search_lenient(
+        &self,
+        mut config: SearchConfig
+    ) -> Result<(SearchResponse, Vec<ItemParseError>), EbayError> {
+        self.retarget(&mut config);
+
+        let started = Instant::now();
+        let body = self.send_search_body(&mut config, 0).await?;
+        let (mut response, failures) = SearchResponse
+            ::from_slice_lenient(&body)?;
+        response.config = Some(config);
+        response.elapsed = started.elapsed();
+
+        if !failures.is_empty() {
+            tracing::warn!(failed = failures.len(), "skipped item summaries that failed to parse");
+        }
+
+        if self.strict && !response.warnings.is_empty() {
+            return Err(EbayError::Warnings(response.warnings));
+        }
+
+        Ok((response, failures))
+    }
+
+    /// Run the search and parse each item summary into a projection such as `ItemIdPrice`,
+    /// skipping every field it doesn't declare. Meant for high volume callers that only need
+    /// a few fields; the cache is bypassed and strict mode still applies.
+    pub async fn search_projected<T: DeserializeOwned>(
+        &self,
+        config: SearchConfig
+    ) -> Result<SearchPage<T>, EbayError> {
+        self.search_as::<SearchPage<T>>(config).await
+    }
+
+    /// Fetch the page after this one by following eBay's own `next` link, sent with the
+    /// headers of the search that produced the response and the given token. Returns None
+    /// on the last page. The link is followed as eBay sent it, even when the client has a
+    /// base URL, and the cache is bypassed.
+    pub async fn fetch_next(
+        &self,
+        response: &SearchResponse,
+        access_token: &str
+    ) -> Result<Option<SearchResponse>, EbayError> {
+        let Some(next) = &response.next else {
+            return Ok(None);
+        };
+
+        // Keep the config in step with the page it now describes
+        let mut config = response.config.clone();
+        if let Some(config) = &mut config {
+            config.set_access_token(access_token);
+            config.search_parameters.insert(
+                String::from("offset"),
+                json!(response.offset + response.limit)
+            );
+        }
+
+        let headers = match &config {
+            Some(config) => config.headers.clone(),
+            None => default_headers(access_token),
+        };
+
+        let started = Instant::now();
+        let body = self.get_bytes(next, &headers, &[] as &[(&str, &str)], 0).await?;
+
+        self.parse_search(config.as_ref(), &body, started.elapsed()).map(Some)
+    }
+
+    /// Send the search and return eBay's response as is, before its body is read, to inspect
+    /// the status and headers or stream the body yourself. A response with an error status
+    /// is returned like any other; only failing to send the request is an error. The client's
+    /// base URL and interceptor apply, but the cache, token refresh and strict mode don't.
+    /// The response holds one of the client's `max_concurrent_requests` permits until it is
+    /// dropped, so read or drop it promptly.
+    pub async fn send_search(&self, config: &SearchConfig) -> Result<reqwest::Response, EbayError> {
+        let url = match &self.base_url {
+            Some(_) => self.endpoint(SEARCH_PATH),
+            None => config.search_url.clone(),
+        };
+
+        // A config without a limit of its own gets the client's
+        let mut parameters = Cow::Borrowed(&config.search_parameters);
+        if !parameters.contains_key("limit") {
+            parameters.to_mut().insert(String::from("limit"), json!(self.default_limit));
+        }
+
+        let permit = self.limiter.clone().acquire_owned().await.expect("request limiter closed");
+
+        let mut response = send_get_response(
+            &self.http,
+            self.interceptor.as_ref(),
+            &url,
+            &config.headers,
+            &*parameters
+        ).await.map_err(EbayError::Http)?;
+
+        // Reading the body consumes the response, so the permit is released only once the
+        // body has been read or the response dropped
+        response.extensions_mut().insert(permit);
+
+        Ok(response)
+    }
+
+    // Point the search at the client's base URL when one is set
+    fn retarget(&self, config: &mut SearchConfig) {
+        if self.base_url.is_some() {
+            config.search_url = self.endpoint(SEARCH_PATH);
+        }
+    }
+
+    // Send the search, refreshing the token and retrying once if eBay reports it expired
+    async fn send_search_body(&self, config: &mut SearchConfig, retries: u32) -> Result<Bytes, EbayError> {
+        match self.fetch_search(config, retries).await {
+            Err(err) if err.is_expired_token() => {
+                let Some(access_token) = self.refreshed_token(Some(config), &config.headers).await else {
+                    return Err(err);
+                };
+
+                config.set_access_token(&access_token?);
+                telemetry::record_retry("expired_token");
+
+                self.fetch_search(config, retries + 1).await
+            }
+            result => result,
+        }
+    }
+
+    /// New token replacing the one eBay rejected as expired in these headers, or None when
+    /// there is no Token Manager to ask. The config's Token Manager is used when it has one,
+    /// the client's otherwise, for searches and item lookups alike. Requests that fail on
+    /// the same token at the same time share a single refresh.
+    pub(crate) async fn refreshed_token(
+        &self,
+        config: Option<&SearchConfig>,
+        headers: &HeaderMap
+    ) -> Option<Result<String, EbayError>> {
+        let manager = config
+            .and_then(|config| config.token_manager.as_ref())
+            .or(self.token_manager.as_ref())?;
+
+        let rejected = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+
+        Some(manager.refresh_rejected(rejected).await)
+    }
+
+    async fn fetch_search(&self, config: &SearchConfig, retries: u32) -> Result<Bytes, EbayError> {
+        receive_bytes(&config.search_url, retries, self.send_search(config)).await
+            .map_err(|err| err.truncate_body(self.max_error_body))
+    }
+
+    fn parse_search(
+        &self,
+        config: Option<&SearchConfig>,
+        body: &[u8],
+        elapsed: Duration
+    ) -> Result<SearchResponse, EbayError> {
+        let mut response: SearchResponse = serde_json
+            ::from_slice(body)?;
+        response.config = config.cloned();
+        response.elapsed = elapsed;
+
+        if self.strict && !response.warnings.is_empty() {
+            return Err(EbayError::Warnings(response.warnings));
+        }
+
+        Ok(response)
+    }
+
+    /// Send a GET request once a permit from the limiter is free, returning the raw body
+    pub(crate) async fn get_bytes<P: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        parameters: &P,
+        retries: u32
+    ) -> Result<Bytes, EbayError> {
+        let _permit = self.limiter.acquire().await.expect("request limiter closed");
+
+        send_get_bytes(&self.http, self.interceptor.as_ref(), url, headers, parameters, retries).await
+            .map_err(|err| err.truncate_body(self.max_error_body))
+    }
+
+    /// Send a GET request once a permit from the limiter is free
+    pub(crate) async fn get<P: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        parameters: &P,
+        retries: u32
+    ) -> Result<Value, EbayError> {
+        let _permit = self.limiter.acquire().await.expect("request limiter closed");
+
+        send_get(&self.http, self.interceptor.as_ref(), url, headers, parameters, retries).await
+            .map_err(|err| err.truncate_body(self.max_error_body))
+    }
+}
+
+// Read an environment variable, treating an empty value as unset
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+// Read a numeric query parameter whether it was set as a number or a string
+pub(crate) fn numeric_param(config: &SearchConfig, key: &str) -> Option<u64> {
+    match config.search_parameters.get(key)? {
+        Value::Number(number) => number.as_u64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_from_the_base_delay() {
+        let retry = RetryConfig::default();
+
+        let delays: Vec<Duration> = (1..=4).map(|attempt| retry.delay(attempt, None)).collect();
+
+        assert_eq!(delays, [500, 1000, 2000, 4000].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn backoff_is_capped_at_large_attempts() {
+        let retry = RetryConfig::default();
+
+        for attempt in [7, 32, 64, 1000, u32::MAX] {
+            assert_eq!(retry.delay(attempt, None), retry.max_backoff, "attempt {}", attempt);
+        }
+    }
+
+    #[test]
+    fn retry_after_is_honored_past_the_cap() {
+        let retry = RetryConfig::default();
+
+        assert_eq!(retry.delay(u32::MAX, Some(Duration::from_secs(120))), Duration::from_secs(120));
+    }
+}
@@ -0,0 +1,220 @@
+//! # Context Module
+//!
+//! The `context` module provides `EndUserContext`, the contents of the `X-EBAY-C-ENDUSERCTX`
+//! header. It tells eBay about the buyer a search is made for, so shipping costs, taxes and
+//! delivery estimates are worked out for them rather than for the server sending the request.
+//!
+//! ## Format
+//!
+//! The header is a comma separated list of `name=value` pairs. `contextualLocation` is itself
+//! a list of pairs, so it is percent-encoded to keep its commas and equals signs apart from
+//! the outer list. The country and postal code are encoded before they are joined into it,
+//! so a separator inside a value stays part of the value:
+//!
+//! ```text
+//! X-EBAY-C-ENDUSERCTX: contextualLocation=country%3DUS%2Czip%3D19406,deviceId=abc123
+//! ```
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use ebay_api_test::context::EndUserContext;
+//! use ebay_api_test::ebay_api::SearchConfig;
+//! # let access_token = "Your-OAuth-Access-Token";
+//!
+//! let context = EndUserContext::new()
+//!     .contextual_location("US", Some("19406"))
+//!     .ip_address("203.0.113.7");
+//!
+//! let config = SearchConfig::builder()
+//!     .query("laptop")
+//!     .access_token(access_token)
+//!     .end_user_context(context)
+//!     .build()?;
+//! # Ok::<(), ebay_api_test::error::EbayError>(())
+//! ```
+
+use std::fmt;
+
+use crate::ebay_api::percent_encode;
+
+/// Header eBay reads the end user context from
+pub const END_USER_CONTEXT_HEADER: &str = "X-EBAY-C-ENDUSERCTX";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Who a request is made on behalf of; every field is optional
+pub struct EndUserContext {
+    pub affiliate_campaign_id: Option<String>,
+    pub affiliate_reference_id: Option<String>,
+    /// Identifier of the buyer's device
+    pub device_id: Option<String>,
+    /// The buyer's IP address, used to locate them when no contextual location is given
+    pub ip_address: Option<String>,
+    /// Two letter country code the buyer is in
+    pub country: Option<String>,
+    /// The buyer's postal code, only sent together with `country`
+    pub zip: Option<String>,
+}
+
+impl EndUserContext {
+    /// Create an empty context
+    pub fn new() -> Self {
+        EndUserContext::default()
+    }
+
+    /// eBay Partner Network campaign ID to credit the sale to
+    pub fn affiliate_campaign_id(mut self, campaign_id: &str) -> Self {
+        self.affiliate_campaign_id = Some(campaign_id.to_string());
+        self
+    }
+
+    /// Free-form reference passed back in eBay Partner Network reports
+    pub fn affiliate_reference_id(mut self, reference_id: &str) -> Self {
+        self.affiliate_reference_id = Some(reference_id.to_string());
+        self
+    }
+
+    /// Identifier of the buyer's device
+    pub fn device_id(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// The buyer's IP address
+    pub fn ip_address(mut self, ip_address: &str) -> Self {
+        self.ip_address = Some(ip_address.to_string());
+        self
+    }
+
+    /// Where the buyer is: a two letter country code and optionally a postal code
+    pub fn contextual_location(mut self, country: &str, zip: Option<&str>) -> Self {
+        self.country = Some(country.to_string());
+        self.zip = zip.map(String::from);
+        self
+    }
+
+    /// True when no field has been set
+    pub fn is_empty(&self) -> bool {
+        self.to_header_value().is_empty()
+    }
+
+    /// The rendered value of the `X-EBAY-C-ENDUSERCTX` header
+    pub fn to_header_value(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+
+        if let Some(campaign_id) = &self.affiliate_campaign_id {
+            parts.push(format!("affiliateCampaignId={}", percent_encode(campaign_id)));
+        }
+
+        if let Some(reference_id) = &self.affiliate_reference_id {
+            parts.push(format!("affiliateReferenceId={}", percent_encode(reference_id)));
+        }
+
+        // Each value is encoded before the pairs are joined, and the joined list once more,
+        // so a comma or equals sign in a value survives eBay decoding the outer list
+        if let Some(country) = &self.country {
+            let location = match &self.zip {
+                Some(zip) => format!("country={},zip={}", percent_encode(country), percent_encode(zip)),
+                None => format!("country={}", percent_encode(country)),
+            };
+            parts.push(format!("contextualLocation={}", percent_encode(&location)));
+        }
+
+        if let Some(device_id) = &self.device_id {
+            parts.push(format!("deviceId={}", percent_encode(device_id)));
+        }
+
+        if let Some(ip_address) = &self.ip_address {
+            parts.push(format!("ipAddress={}", percent_encode(ip_address)));
+        }
+
+        parts.join(",")
+    }
+}
+
+impl fmt::Display for EndUserContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_header_value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full() -> EndUserContext {
+        EndUserContext::new()
+            .affiliate_campaign_id("5338")
+            .affiliate_reference_id("ref")
+            .contextual_location("US", Some("19406"))
+            .device_id("abc123")
+            .ip_address("203.0.113.7")
+    }
+
+    #[test]
+    fn every_field_renders_in_order() {
+        assert_eq!(
+            full().to_header_value(),
+            "affiliateCampaignId=5338,affiliateReferenceId=ref,\
+             contextualLocation=country%3DUS%2Czip%3D19406,deviceId=abc123,ipAddress=203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn every_combination_renders_only_the_fields_set() {
+        let fields = [
+            "affiliateCampaignId=",
+            "affiliateReferenceId=",
+            "contextualLocation=",
+            "deviceId=",
+            "ipAddress=",
+        ];
+
+        for mask in 0..(1 << fields.len()) {
+            let set = |bit: usize| mask & (1 << bit) != 0;
+            let full = full();
+            let context = EndUserContext {
+                affiliate_campaign_id: full.affiliate_campaign_id.filter(|_| set(0)),
+                affiliate_reference_id: full.affiliate_reference_id.filter(|_| set(1)),
+                country: full.country.filter(|_| set(2)),
+                zip: full.zip.filter(|_| set(2)),
+                device_id: full.device_id.filter(|_| set(3)),
+                ip_address: full.ip_address.filter(|_| set(4)),
+            };
+
+            let value = context.to_header_value();
+            let rendered: Vec<&str> = fields
+                .iter()
+                .filter(|field| value.split(',').any(|part| part.starts_with(*field)))
+                .copied()
+                .collect();
+            let expected: Vec<&str> = (0..fields.len()).filter(|bit| set(*bit)).map(|bit| fields[bit]).collect();
+            assert_eq!(rendered, expected, "{}", value);
+            assert_eq!(value.split(',').filter(|part| !part.is_empty()).count(), expected.len(), "{}", value);
+            assert_eq!(context.is_empty(), mask == 0);
+        }
+    }
+
+    #[test]
+    fn country_without_zip() {
+        let context = EndUserContext::new().contextual_location("DE", None);
+
+        assert_eq!(context.to_header_value(), "contextualLocation=country%3DDE");
+    }
+
+    #[test]
+    fn zip_without_country_is_dropped() {
+        let context = EndUserContext { zip: Some(String::from("19406")), ..EndUserContext::default() };
+
+        assert_eq!(context.to_header_value(), "");
+        assert!(context.is_empty());
+    }
+
+    #[test]
+    fn separators_in_location_values_are_escaped_before_joining() {
+        let context = EndUserContext::new().contextual_location("US", Some("194,06=x"));
+
+        // Decoding the outer list once leaves `country=US,zip=194%2C06%3Dx`, two pairs
+        assert_eq!(context.to_header_value(), "contextualLocation=country%3DUS%2Czip%3D194%252C06%253Dx");
+    }
+}
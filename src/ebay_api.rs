@@ -5,12 +5,12 @@
 //! ## Overview
 //!
 //! This module contains a `SearchConfig` structure to hold the data required to make a search request,
-//! along with a function `post_query` to perform the actual API request.
+//! along with a function `run_search` to perform the actual API request.
 //!
 //! ## Example Usage
 //!
-//! ```rust
-//! use ebay_api::{SearchConfig, post_query};
+//! ```no_run
+//! use ebay_api_test::ebay_api::{SearchConfig, run_search};
 //!
 //! #[tokio::main]
 //! async fn main() {
@@ -24,7 +24,7 @@
 //!     let config = SearchConfig::new(search_query, access_token);
 //!
 //!     // Perform the API request and handle the result
-//!     if let Err(err) = post_query(config).await {
+//!     if let Err(err) = run_search(config).await {
 //!         eprintln!("Error: {}", err);
 //!     }
 //! }
@@ -38,30 +38,88 @@
 //!
 //! - `app_id`: eBay developer application ID.
 //! - `cert_id`: eBay developer certificate ID.
-//! - `search_url`: The URL for the eBay API endpoint for item search.
+//! - `environment`: The `Environment` (sandbox or production) the search is sent to.
+//! - `search_url`: The URL for the eBay API endpoint for item search, derived from `environment`.
 //! - `headers`: HeaderMap containing necessary headers for the API request (content type, authorization).
 //! - `search_parameters`: Map containing search parameters such as query and limit.
+//! - `token_manager`: Optional `TokenManager` used to refresh an expired access token.
 //!
 //! ### Methods
 //!
 //! - `new(query: serde_json::Value, access_token: String) -> Self`: Creates a new `SearchConfig` instance.
+//! - `builder() -> SearchConfigBuilder`: Starts a `SearchConfigBuilder` with the default settings.
+//! - `environment(environment: Environment) -> Self`: Targets sandbox or production.
+//! - `token_manager(manager: Arc<TokenManager>) -> Self`: Attaches a `TokenManager` for automatic token refresh.
+//! - `with_headers(extra: HeaderMap) -> Self`: Merges extra headers, except the reserved
+//!   `Authorization` and `Content-Type`.
+//! - `extra_param(key: &str, value: impl Into<Value>) -> Self`: Sets any other query parameter.
+//! - `clear_param(key: &str) -> Self`: Removes a query parameter; `clear_filters()` removes
+//!   `filter`, `aspect_filter` and `compatibility_filter`.
+//! - `to_request_builder(client: &reqwest::Client) -> reqwest::RequestBuilder`: Returns the
+//!   unsent request so it can be adjusted and sent by hand.
+//! - `set_access_token(access_token: &str)`: Replaces the token in the authorization header.
+//!
+//! ## `SearchConfigBuilder` Structure
+//!
+//! `SearchConfigBuilder::default()` targets the sandbox environment and the `EBAY_US` marketplace,
+//! and without a `limit` the client's `default_limit` of 50 results per page is sent; set only
+//! the fields that differ and call `build()`.
+//!
+//! ```rust
+//! # use ebay_api_test::ebay_api::SearchConfig;
+//! # let access_token = "Your-OAuth-Access-Token";
+//! let config = SearchConfig::builder()
+//!     .query("laptop")
+//!     .access_token(access_token)
+//!     .limit(20)
+//!     .build()?;
+//! # Ok::<(), ebay_api_test::error::EbayError>(())
+//! ```
 //!
-//! ## `post_query` Function
+//! ## `search` Function
 //!
-//! The `post_query` function performs an asynchronous API request using the provided `SearchConfig`.
+//! `search(query, filter, access_token, environment)` covers the common case in one call: it
+//! builds the config from the defaults plus the given `SearchFilter` and runs it.
+//!
+//! ## `run_search` Function
+//!
+//! The `run_search` function performs an asynchronous GET request using the provided `SearchConfig`.
+//! When eBay answers 401 with an expired-token error and the config carries a `TokenManager`, the
+//! token is refreshed and the request is retried once; a second 401 is returned as an error.
 //!
 //! ### Parameters
 //!
 //! - `config`: A `SearchConfig` instance containing the configuration for the API request.
 //!
+//! ### Instrumentation
+//!
+//! Every request sent to eBay runs inside an `ebay_request` `tracing` span with the fields
+//! `url`, `retries`, `status` and `duration_ms`. Nothing is emitted unless the application
+//! installs a `tracing` subscriber.
+//!
 //! ### Returns
 //!
-//! - `Result<(), reqwest::Error>`: A Result indicating the success or failure of the API request.
+//! - `Result<SearchResponse, EbayError>`: The parsed response, or the error that stopped the request.
+//!
+//! `run_search_raw` behaves the same but returns `(SearchResponse, Bytes)`, keeping the original
+//! response body next to the parsed one.
+//!
+//! `run_search_as::<T>` parses the response into any `DeserializeOwned` type instead, for fields
+//! `SearchResponse` doesn't model.
+//!
+//! `search_across` runs one query on several marketplaces at once and returns a result per
+//! marketplace, so a marketplace rejecting the token doesn't hide the others.
+//!
+//! `run_search` creates a fresh `EbayClient` for every call; hold on to an `EbayClient` and call
+//! `search` instead to reuse connections and share a response `Cache` between searches.
+//!
+//! `post_query`, `post_query_raw` and `post_query_as` are deprecated aliases kept from before
+//! the rename; the search has always been a GET.
 //!
 //! ### Example
 //!
-//! ```rust
-//! use ebay_api::{SearchConfig, post_query};
+//! ```no_run
+//! use ebay_api_test::ebay_api::{SearchConfig, run_search};
 //!
 //! #[tokio::main]
 //! async fn main() {
@@ -75,88 +133,1342 @@
 //!     let config = SearchConfig::new(search_query, access_token);
 //!
 //!     // Perform the API request and handle the result
-//!     if let Err(err) = post_query(config).await {
+//!     if let Err(err) = run_search(config).await {
 //!         eprintln!("Error: {}", err);
 //!     }
 //! }
-//! ````
-
-#[allow(unused)]
-pub mod ebay_api {
-    use std::collections::HashMap;
-    use reqwest::header::{ self, HeaderMap };
-    use serde_json::{ Value, json };
-
-    #[derive(Debug)]
-    /// Search Config Structure to hold the data we will use to
-    /// make the request
-    pub struct SearchConfig {
-        pub app_id: String,
-        pub cert_id: String,
-        pub search_url: String,
-        pub headers: header::HeaderMap,
-        pub search_parameters: serde_json::Map<String, serde_json::Value>,
-    }
-
-    impl SearchConfig {
-        /// Create New Search Config
-        /// query -> search query, item you are searching for
-        /// access_token -> OAuth access token from eBay
-
-        pub fn new(query: serde_json::Value, access_token: String) -> Self {
-            // Make an empty header map and insert the content type and authorization headers
-
-            let mut headers = HeaderMap::new();
-            headers.insert(
-                header::CONTENT_TYPE,
-                header::HeaderValue::from_static("application/json").to_owned()
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use bytes::Bytes;
+use reqwest::header::{ self, HeaderMap };
+use serde::de::DeserializeOwned;
+use serde_json::{ Value, json };
+
+use crate::client::{ numeric_param, EbayClient, DEFAULT_PAGE_SIZE, MAX_RESULT_WINDOW };
+use crate::context::{ EndUserContext, END_USER_CONTEXT_HEADER };
+use crate::error::{ EbayError, ValidationError };
+use crate::fieldgroups::{ render_fieldgroups, FieldGroup };
+use crate::filter::{ aspect_entry, SearchFilter, SortOrder };
+use crate::interceptor::RequestInterceptor;
+use crate::marketplace::Marketplace;
+use crate::models::SearchResponse;
+use crate::telemetry;
+use crate::token::{ expose, redacted, secret, Secret, TokenManager };
+
+/// Path of the Browse API item summary search endpoint
+pub const SEARCH_PATH: &str = "/buy/browse/v1/item_summary/search";
+/// Path of the Browse API item detail endpoint
+pub const ITEM_PATH: &str = "/buy/browse/v1/item";
+/// Path of the OAuth client credentials token endpoint
+pub const TOKEN_PATH: &str = "/identity/v1/oauth2/token";
+
+/// Header eBay uses to select the marketplace a search runs against
+pub const MARKETPLACE_HEADER: &str = "X-EBAY-C-MARKETPLACE-ID";
+
+/// Shortest query eBay accepts, counted after trimming whitespace
+pub const MIN_QUERY_LENGTH: usize = 2;
+
+/// Most results eBay returns on a single page
+pub const MAX_LIMIT: u64 = 200;
+
+/// Headers the crate manages itself, which `with_headers` never overrides.
+///
+/// Precedence for `Authorization` is fixed: the crate's own token always wins. Values
+/// passed to `with_headers` are dropped, and if the header map ends up holding more than
+/// one value anyway (e.g. after editing `headers` by hand), only the first is sent, so a
+/// request never carries two credentials.
+pub const RESERVED_HEADERS: [header::HeaderName; 2] = [header::AUTHORIZATION, header::CONTENT_TYPE];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// eBay environment a request is sent to. Sandbox and production use separate
+/// hosts and separate developer keysets, so a config only ever talks to one of them.
+pub enum Environment {
+    #[default]
+    Sandbox,
+    Production,
+}
+
+impl Environment {
+    /// Host serving the REST APIs for this environment
+    pub fn api_host(&self) -> &'static str {
+        match self {
+            Environment::Sandbox => "api.sandbox.ebay.com",
+            Environment::Production => "api.ebay.com",
+        }
+    }
+
+    /// Scheme and host every endpoint path is appended to
+    pub fn base_url(&self) -> String {
+        format!("https://{}", self.api_host())
+    }
+
+    /// Browse API item summary search endpoint
+    pub fn search_url(&self) -> String {
+        format!("{}{}", self.base_url(), SEARCH_PATH)
+    }
+
+    /// Browse API item detail endpoint
+    pub fn item_url(&self) -> String {
+        format!("{}{}", self.base_url(), ITEM_PATH)
+    }
+
+    /// OAuth client credentials token endpoint
+    pub fn token_url(&self) -> String {
+        format!("{}{}", self.base_url(), TOKEN_PATH)
+    }
+}
+
+impl std::str::FromStr for Environment {
+    type Err = EbayError;
+
+    /// Parse `sandbox` or `production` (also `prod`), ignoring case
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "sandbox" => Ok(Environment::Sandbox),
+            "production" | "prod" => Ok(Environment::Production),
+            other => Err(EbayError::Config(format!("unknown environment '{}'", other))),
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Search Config Structure to hold the data we will use to
+/// make the request. Debug output redacts the app and cert IDs and the access token.
+pub struct SearchConfig {
+    pub app_id: String,
+    pub cert_id: Secret,
+    pub environment: Environment,
+    pub search_url: String,
+    pub headers: header::HeaderMap,
+    pub search_parameters: serde_json::Map<String, serde_json::Value>,
+    pub token_manager: Option<Arc<TokenManager>>,
+}
+
+impl fmt::Debug for SearchConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchConfig")
+            .field("app_id", &redacted(&self.app_id))
+            .field("cert_id", &redacted(expose(&self.cert_id)))
+            .field("environment", &self.environment)
+            .field("search_url", &self.search_url)
+            .field("headers", &self.headers)
+            .field("search_parameters", &self.search_parameters)
+            .field("token_manager", &self.token_manager)
+            .finish()
+    }
+}
+
+impl SearchConfig {
+    /// Create New Search Config
+    /// query -> search query, item you are searching for
+    /// access_token -> OAuth access token from eBay
+    pub fn new(query: serde_json::Value, access_token: String) -> Self {
+        SearchConfigBuilder::default()
+            .query(query)
+            .access_token(access_token)
+            .assemble()
+    }
+
+    /// Start from the defaults and override only what you need
+    pub fn builder() -> SearchConfigBuilder {
+        SearchConfigBuilder::default()
+    }
+
+    /// Send the search to the given environment instead of the sandbox
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self.search_url = environment.search_url();
+        self
+    }
+
+    /// Attach a Token Manager so an expired access token is refreshed
+    /// and the request retried once instead of failing
+    pub fn token_manager(mut self, manager: Arc<TokenManager>) -> Self {
+        self.token_manager = Some(manager);
+        self
+    }
+
+    /// Set any query parameter the config has no dedicated setter for,
+    /// e.g. `extra_param("fieldgroups", "ASPECT_REFINEMENTS")`
+    pub fn extra_param(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.search_parameters.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Remove a query parameter, e.g. `clear_param("sort")` on a clone of a config to go back
+    /// to eBay's best match order. Removing a parameter that isn't set does nothing.
+    pub fn clear_param(mut self, key: &str) -> Self {
+        self.search_parameters.remove(key);
+        self
+    }
+
+    /// Remove every filtering parameter: `filter`, `aspect_filter` and `compatibility_filter`.
+    /// The query, categories, paging and sort order are kept.
+    pub fn clear_filters(self) -> Self {
+        self.clear_param("filter")
+            .clear_param("aspect_filter")
+            .clear_param("compatibility_filter")
+    }
+
+    /// Merge extra headers (tracing IDs, experiment flags, ...) into the request.
+    /// A header already in the config is replaced, and every value given for one name is
+    /// kept. `Authorization` and `Content-Type` are reserved: values for them are ignored,
+    /// so the token set through `new`, the builder or `set_access_token` is always the one sent.
+    pub fn with_headers(mut self, extra: HeaderMap) -> Self {
+        merge_headers(&mut self.headers, extra);
+        self
+    }
+
+    /// Send the named headers first, in the given order, followed by the rest in their
+    /// current order, for proxies that insist on a particular header order. Names not in
+    /// the config are skipped. Values and the number of values per header don't change.
+    ///
+    /// Only the order can be controlled, not the casing: `HeaderMap` stores names in lower
+    /// case, which is how reqwest sends them over HTTP/1.1, and HTTP/2 requires lower case
+    /// names anyway. reqwest may also add headers of its own, such as `Accept-Encoding`,
+    /// after these.
+    pub fn header_order(mut self, order: &[header::HeaderName]) -> Self {
+        let mut ordered = HeaderMap::with_capacity(self.headers.len());
+
+        for name in order {
+            for value in self.headers.get_all(name) {
+                ordered.append(name.clone(), value.clone());
+            }
+        }
+
+        for name in self.headers.keys() {
+            if ordered.contains_key(name) {
+                continue;
+            }
+
+            for value in self.headers.get_all(name) {
+                ordered.append(name.clone(), value.clone());
+            }
+        }
+
+        self.headers = ordered;
+        self
+    }
+
+    /// The GET request this config describes, not yet sent. An escape hatch for anything
+    /// the crate doesn't model: add a body, more headers or a timeout, or change the method,
+    /// then send it yourself. Token refresh, caching and retries are not applied to it.
+    pub fn to_request_builder(&self, client: &reqwest::Client) -> reqwest::RequestBuilder {
+        client
+            .get(&self.search_url)
+            .headers(self.headers.clone())
+            .query(&self.search_parameters)
+    }
+
+    /// Replace the access token used in the authorization header
+    pub fn set_access_token(&mut self, access_token: &str) {
+        self.headers.insert(header::AUTHORIZATION, bearer(access_token));
+    }
+
+    /// Check every constraint eBay puts on a search and return all the problems found,
+    /// empty when the search is fine to send:
+    ///
+    /// - a keyword query has at least two characters
+    /// - `limit` is between 1 and 200
+    /// - `offset` + `limit` stays within the 10,000 result window
+    /// - a price range in the filter comes with a `priceCurrency`
+    /// - there is something to search for: a query, categories, a GTIN or an ePID
+    /// - an aspect filter names its category
+    /// - the `Authorization` and marketplace headers are present, with a single value each
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut problems = Vec::new();
+        let mut problem = |parameter: &str, message: String| {
+            problems.push(ValidationError { parameter: parameter.to_string(), message });
+        };
+
+        for name in [header::AUTHORIZATION, marketplace_header_name()] {
+            match self.headers.get_all(&name).iter().count() {
+                0 => problem(name.as_str(), String::from("header is missing")),
+                1 => {}
+                count => problem(name.as_str(), format!("header has {} values, eBay expects one", count)),
+            }
+        }
+
+        if let Some(Value::String(query)) = self.search_parameters.get("q") {
+            if query.trim().chars().count() < MIN_QUERY_LENGTH {
+                problem(
+                    "q",
+                    format!(
+                        "query '{}' must be at least {} characters long",
+                        query.trim(),
+                        MIN_QUERY_LENGTH
+                    )
+                );
+            }
+        }
+
+        let limit = numeric_param(self, "limit");
+        if let Some(limit) = limit {
+            if limit == 0 || limit > MAX_LIMIT {
+                problem("limit", format!("limit {} must be between 1 and {}", limit, MAX_LIMIT));
+            }
+        }
+
+        let offset = numeric_param(self, "offset").unwrap_or(0);
+        let window = offset + limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if window > MAX_RESULT_WINDOW {
+            problem(
+                "offset",
+                format!(
+                    "offset {} plus limit reaches {}, past the {} results eBay returns",
+                    offset,
+                    window,
+                    MAX_RESULT_WINDOW
+                )
             );
+        }
+
+        let has_search_term = ["q", "category_ids", "gtin", "epid"]
+            .iter()
+            .any(|key| self.search_parameters.contains_key(*key));
+        if !has_search_term {
+            problem("q", String::from("a search needs a query, category_ids, gtin or epid"));
+        }
+
+        if let Some(Value::String(aspect_filter)) = self.search_parameters.get("aspect_filter") {
+            if !aspect_filter.starts_with("categoryId:") {
+                problem("aspect_filter", String::from("an aspect filter needs a category in category_ids"));
+            }
+        }
+
+        if let Some(Value::String(filter)) = self.search_parameters.get("filter") {
+            let fields: Vec<&str> = filter
+                .split(',')
+                .map(|part| part.split(':').next().unwrap_or_default().trim())
+                .collect();
+
+            if fields.contains(&"price") && !fields.contains(&"priceCurrency") {
+                problem("filter", String::from("a price range needs a priceCurrency"));
+            }
+        }
+
+        problems
+    }
+}
+
+#[derive(Clone)]
+/// Builder for `SearchConfig`. The defaults target the sandbox and the US marketplace,
+/// and leave the page size to the client: without `limit`, `EbayClient` sends its
+/// `default_limit`, `DEFAULT_PAGE_SIZE` unless changed. Debug output redacts the
+/// credentials like `SearchConfig`.
+pub struct SearchConfigBuilder {
+    pub app_id: String,
+    pub cert_id: Secret,
+    pub query: Option<Value>,
+    pub access_token: Secret,
+    pub environment: Environment,
+    pub search_url: Option<String>,
+    /// Results per page, None to use the client's `default_limit`
+    pub limit: Option<u32>,
+    pub marketplace: Marketplace,
+    pub locale: Option<String>,
+    pub category_ids: Vec<String>,
+    pub fieldgroups: Option<String>,
+    pub gtin: Option<String>,
+    /// Aspect name and value pairs rendered into `aspect_filter`
+    pub aspects: Vec<(String, String)>,
+    pub sort: Option<SortOrder>,
+    pub auto_correct: bool,
+    pub end_user_context: Option<EndUserContext>,
+    pub filter: SearchFilter,
+    pub token_manager: Option<Arc<TokenManager>>,
+}
+
+impl Default for SearchConfigBuilder {
+    fn default() -> Self {
+        SearchConfigBuilder {
+            app_id: String::new(),
+            cert_id: secret(String::new()),
+            query: None,
+            access_token: secret(String::new()),
+            environment: Environment::Sandbox,
+            search_url: None,
+            limit: None,
+            marketplace: Marketplace::default(),
+            locale: None,
+            category_ids: Vec::new(),
+            fieldgroups: None,
+            gtin: None,
+            aspects: Vec::new(),
+            sort: None,
+            auto_correct: false,
+            end_user_context: None,
+            filter: SearchFilter::default(),
+            token_manager: None,
+        }
+    }
+}
+
+impl fmt::Debug for SearchConfigBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchConfigBuilder")
+            .field("app_id", &redacted(&self.app_id))
+            .field("cert_id", &redacted(expose(&self.cert_id)))
+            .field("query", &self.query)
+            .field("access_token", &redacted(expose(&self.access_token)))
+            .field("environment", &self.environment)
+            .field("search_url", &self.search_url)
+            .field("limit", &self.limit)
+            .field("marketplace", &self.marketplace)
+            .field("locale", &self.locale)
+            .field("category_ids", &self.category_ids)
+            .field("fieldgroups", &self.fieldgroups)
+            .field("gtin", &self.gtin)
+            .field("aspects", &self.aspects)
+            .field("sort", &self.sort)
+            .field("auto_correct", &self.auto_correct)
+            .field("end_user_context", &self.end_user_context)
+            .field("filter", &self.filter)
+            .field("token_manager", &self.token_manager)
+            .finish()
+    }
+}
+
+impl SearchConfigBuilder {
+    /// eBay developer application ID (client ID). Empty unless set here or taken from the
+    /// client's Token Manager by `EbayClient::search_config`.
+    pub fn app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = app_id.into();
+        self
+    }
+
+    /// eBay developer certificate ID (client secret), empty by default like `app_id`
+    pub fn cert_id(mut self, cert_id: impl Into<String>) -> Self {
+        self.cert_id = secret(cert_id);
+        self
+    }
+
+    /// Item you are searching for
+    pub fn query(mut self, query: impl Into<Value>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// OAuth access token from eBay
+    pub fn access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = secret(access_token);
+        self
+    }
+
+    /// Sandbox or production
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Send the search to this URL instead of the environment's search endpoint
+    pub fn search_url(mut self, search_url: &str) -> Self {
+        self.search_url = Some(search_url.to_string());
+        self
+    }
+
+    /// Number of results per page, overriding the client's `default_limit`
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Marketplace to search, e.g. `Marketplace::UnitedKingdom`. Its ID is sent in the
+    /// `X-EBAY-C-MARKETPLACE-ID` header and it provides the defaults for the locale and for
+    /// the currency of a price range, so they always agree with the header.
+    pub fn marketplace(mut self, marketplace: Marketplace) -> Self {
+        self.marketplace = marketplace;
+        self
+    }
+
+    /// Language for listing titles and aspect names, e.g. `de-DE`, sent as
+    /// `Accept-Language` and `Content-Language`. When unset, the marketplace's
+    /// language is used.
+    pub fn locale(mut self, locale: &str) -> Self {
+        self.locale = Some(locale.to_string());
+        self
+    }
+
+    /// Only search within these categories, e.g. `&["9355"]` for cell phones
+    pub fn category_ids(mut self, category_ids: &[&str]) -> Self {
+        self.category_ids = category_ids.iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    /// Containers eBay includes in the response, e.g. item summaries plus aspect refinements.
+    /// Fails with a `Validation` error for combinations eBay doesn't support, such as
+    /// `COMPACT` with `EXTENDED`; see the `fieldgroups` module for the full matrix.
+    pub fn fieldgroups(mut self, groups: &[FieldGroup]) -> Result<Self, EbayError> {
+        self.fieldgroups = Some(render_fieldgroups(groups)?);
+        Ok(self)
+    }
+
+    /// Only listings for the product with this Global Trade Item Number (UPC, EAN or ISBN).
+    /// Can be combined with a query, categories and aspects.
+    pub fn gtin(mut self, gtin: &str) -> Self {
+        self.gtin = Some(gtin.trim().to_string());
+        self
+    }
+
+    /// Only listings whose aspect has this value, e.g. `aspect("Color", "Black")`, sent in
+    /// `aspect_filter` with commas, pipes, braces and backslashes in the value escaped.
+    /// eBay only applies aspects within a category, so the first of `category_ids` is used
+    /// and `build` fails without one.
+    pub fn aspect(mut self, name: &str, value: &str) -> Self {
+        self.aspects.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Only listings of this brand, shorthand for `aspect("Brand", brand)`
+    pub fn brand(self, brand: &str) -> Self {
+        self.aspect("Brand", brand)
+    }
+
+    /// Only listings with this manufacturer part number, shorthand for `aspect("MPN", mpn)`.
+    /// With `brand` and `gtin` this pins a search to one catalog product.
+    pub fn mpn(self, mpn: &str) -> Self {
+        self.aspect("MPN", mpn)
+    }
+
+    /// Order of the results, e.g. `SortOrder::PriceAscending` for the cheapest first
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.sort = Some(sort);
+        self
+    }
 
-            let auth_header_value = format!("Bearer {}", access_token);
+    /// Let eBay correct misspelled keywords (`auto_correct=KEYWORD`). The query it searched
+    /// for instead is in `SearchResponse::corrected_query`.
+    pub fn auto_correct(mut self, enabled: bool) -> Self {
+        self.auto_correct = enabled;
+        self
+    }
+
+    /// Buyer the search is made for, sent as `X-EBAY-C-ENDUSERCTX` so shipping and tax
+    /// estimates match their location
+    pub fn end_user_context(mut self, context: EndUserContext) -> Self {
+        self.end_user_context = Some(context);
+        self
+    }
+
+    /// Filter applied to the results, replacing any filter set before
+    pub fn filter(mut self, filter: SearchFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Country the buyer is in, e.g. `US`. Shipping costs and delivery
+    /// options in the results are estimated for this destination.
+    pub fn delivery_country(mut self, country: &str) -> Self {
+        self.filter = self.filter.delivery_country(country);
+        self
+    }
+
+    /// Postal code the buyer is in, used with `delivery_country` for shipping estimates
+    pub fn delivery_postal_code(mut self, postal_code: &str) -> Self {
+        self.filter = self.filter.delivery_postal_code(postal_code);
+        self
+    }
+
+    /// Token Manager used to refresh an expired access token
+    pub fn token_manager(mut self, manager: Arc<TokenManager>) -> Self {
+        self.token_manager = Some(manager);
+        self
+    }
+
+    /// Assemble the settings into a `SearchConfig` and check it with `SearchConfig::validate`.
+    /// Fails with a `Validation` error listing every problem found, which eBay would
+    /// otherwise reject one at a time with much less helpful messages. A locale that can't
+    /// be sent as a header, e.g. one containing a line break, is reported the same way.
+    pub fn build(self) -> Result<SearchConfig, EbayError> {
+        let mut problems = Vec::new();
+        if let Some(locale) = self.locale.as_deref() {
+            if header::HeaderValue::from_str(locale).is_err() {
+                problems.push(ValidationError {
+                    parameter: header::ACCEPT_LANGUAGE.to_string(),
+                    message: format!("locale {:?} is not a valid header value", locale),
+                });
+            }
+        }
+
+        let config = self.assemble();
+        problems.extend(config.validate());
+
+        if !problems.is_empty() {
+            let messages: Vec<String> = problems
+                .iter()
+                .map(|problem| problem.to_string())
+                .collect();
+
+            return Err(EbayError::Validation(messages.join("; ")));
+        }
+
+        Ok(config)
+    }
+
+    // Assemble the headers and query parameters without validating them
+    fn assemble(self) -> SearchConfig {
+        let mut headers = default_headers(expose(&self.access_token));
+        headers.insert(
+            MARKETPLACE_HEADER,
+            header::HeaderValue::from_static(self.marketplace.id())
+        );
+
+        // An invalid locale is left out here and reported by build
+        let locale = self.locale.as_deref().unwrap_or(self.marketplace.locale());
+        if let Ok(value) = header::HeaderValue::from_str(locale) {
+            headers.insert(header::ACCEPT_LANGUAGE, value.clone());
+            headers.insert(header::CONTENT_LANGUAGE, value);
+        }
+
+        if let Some(context) = self.end_user_context.filter(|context| !context.is_empty()) {
             headers.insert(
-                header::AUTHORIZATION,
-                header::HeaderValue::from_str(&auth_header_value).unwrap()
+                END_USER_CONTEXT_HEADER,
+                header::HeaderValue::from_str(&context.to_header_value()).unwrap()
             );
+        }
 
-            let mut search_parameters: serde_json::Map<String, Value> = serde_json::Map::new();
+        let mut search_parameters: serde_json::Map<String, Value> = serde_json::Map::new();
+        if let Some(query) = self.query {
             search_parameters.insert(String::from("q"), query);
-            search_parameters.insert(String::from("limit"), json!("5"));
-
-            SearchConfig {
-                app_id: String::from("AdamCarr-mtgcardf-SBX-3ac219c73-c36c6538"),
-                cert_id: String::from("SBX-ac219c739b47-816b-43f8-964f-6b1a"),
-                headers,
-                search_url: String::from(
-                    "https://api.sandbox.ebay.com/buy/browse/v1/item_summary/search"
-                ),
-                search_parameters,
+        }
+        // Numbers go in as JSON numbers; numeric_param and validate also accept strings
+        // such as extra_param("limit", "5"), and both are sent as an unquoted limit=5
+        if let Some(limit) = self.limit {
+            search_parameters.insert(String::from("limit"), json!(limit));
+        }
+        if !self.category_ids.is_empty() {
+            search_parameters.insert(String::from("category_ids"), json!(self.category_ids.join(",")));
+        }
+        if let Some(fieldgroups) = self.fieldgroups {
+            search_parameters.insert(String::from("fieldgroups"), json!(fieldgroups));
+        }
+        if let Some(sort) = self.sort {
+            search_parameters.insert(String::from("sort"), json!(sort.as_str()));
+        }
+        if self.auto_correct {
+            search_parameters.insert(String::from("auto_correct"), json!("KEYWORD"));
+        }
+        if let Some(gtin) = self.gtin {
+            search_parameters.insert(String::from("gtin"), json!(gtin));
+        }
+        if !self.aspects.is_empty() {
+            // Without a category the filter is left without one, for validate to report
+            let mut parts: Vec<String> = self.category_ids
+                .first()
+                .map(|category_id| format!("categoryId:{}", category_id))
+                .into_iter()
+                .collect();
+            for (name, value) in &self.aspects {
+                parts.push(aspect_entry(name, value));
             }
+            search_parameters.insert(String::from("aspect_filter"), json!(parts.join(",")));
+        }
+
+        // eBay ignores a price range without a currency and a postal code without a country,
+        // so fill them in from the marketplace
+        let mut filter = self.filter;
+        let has_price = filter.min_price.is_some() || filter.max_price.is_some();
+        if has_price && filter.price_currency.is_none() {
+            filter.price_currency = Some(self.marketplace.currency().to_string());
         }
+        if filter.delivery_postal_code.is_some() && filter.delivery_country.is_none() {
+            filter.delivery_country = Some(self.marketplace.country().to_string());
+        }
+
+        if !filter.is_empty() {
+            search_parameters.insert(String::from("filter"), json!(filter.to_filter_string()));
+        }
+
+        SearchConfig {
+            app_id: self.app_id,
+            cert_id: self.cert_id,
+            environment: self.environment,
+            search_url: self.search_url.unwrap_or_else(|| self.environment.search_url()),
+            headers,
+            search_parameters,
+            token_manager: self.token_manager,
+        }
+    }
+}
+
+/// Render search parameters as a query string, sorted by key and percent-encoded, e.g.
+/// `limit=5&q=red%20shoes`. String values are written without their JSON quotes. Sorting
+/// keeps the output identical for the same parameters, whatever order they were set in.
+pub(crate) fn query_string(parameters: &serde_json::Map<String, Value>) -> String {
+    let mut pairs: Vec<(&String, &Value)> = parameters.iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut query = String::new();
+    for (key, value) in pairs {
+        if !query.is_empty() {
+            query.push('&');
+        }
+
+        percent_encode_into(&mut query, key);
+        query.push('=');
+        match value {
+            Value::String(value) => percent_encode_into(&mut query, value),
+            other => percent_encode_into(&mut query, &other.to_string()),
+        }
+    }
+
+    query
+}
+
+/// The text with everything but RFC 3986 unreserved characters percent-encoded
+pub(crate) fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    percent_encode_into(&mut encoded, text);
+    encoded
+}
+
+// Append the text with everything but RFC 3986 unreserved characters percent-encoded
+fn percent_encode_into(out: &mut String, text: &str) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => {
+                out.push('%');
+                out.push(HEX[(byte >> 4) as usize] as char);
+                out.push(HEX[(byte & 0x0f) as usize] as char);
+            }
+        }
+    }
+}
+
+/// Merge extra headers into a header map the way `SearchConfig::with_headers` documents:
+/// existing headers are replaced, every value is kept and reserved headers are skipped
+pub(crate) fn merge_headers(headers: &mut HeaderMap, extra: HeaderMap) {
+    let mut current: Option<header::HeaderName> = None;
+
+    for (name, value) in extra {
+        // A None name is another value for the header named just before
+        if let Some(name) = name {
+            current = if RESERVED_HEADERS.contains(&name) {
+                None
+            } else {
+                headers.insert(name.clone(), value);
+                Some(name)
+            };
+            continue;
+        }
+
+        if let Some(name) = &current {
+            headers.append(name, value);
+        }
+    }
+}
+
+fn marketplace_header_name() -> header::HeaderName {
+    header::HeaderName::from_bytes(MARKETPLACE_HEADER.as_bytes()).unwrap()
+}
+
+// Drop every value of the header but the first
+fn keep_first_value(headers: &mut HeaderMap, name: header::HeaderName) {
+    if headers.get_all(&name).iter().count() <= 1 {
+        return;
+    }
+
+    tracing::warn!(header = %name, "dropping duplicate values of a single-valued header");
+
+    if let Some(first) = headers.get(&name).cloned() {
+        headers.insert(name, first);
+    }
+}
+
+/// Header map sent with every Browse API request
+pub(crate) fn default_headers(access_token: &str) -> HeaderMap {
+    // Make an empty header map and insert the content type and authorization headers
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json").to_owned()
+    );
+    // Ask for JSON explicitly so a proxy doing content negotiation can't pick another format
+    headers.insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+    headers.insert(header::AUTHORIZATION, bearer(access_token));
+
+    headers
+}
+
+// The token is used verbatim apart from surrounding whitespace, which is easy to pick up
+// when pasting a token into config.toml or an environment variable
+pub(crate) fn bearer(access_token: &str) -> header::HeaderValue {
+    let auth_header_value = format!("Bearer {}", access_token.trim());
+    let mut value = header::HeaderValue::from_str(&auth_header_value).unwrap();
+    // Sensitive values print as `Sensitive` in the Debug output of a config or header map
+    value.set_sensitive(true);
+    value
+}
+
+/// Run the search described by the config and return the parsed response. The request is
+/// a GET. If eBay reports the access token as expired and the config has a Token Manager,
+/// the token is refreshed and the request is retried once.
+pub async fn run_search(config: SearchConfig) -> Result<SearchResponse, EbayError> {
+    EbayClient::new().search(config).await
+}
+
+/// Same as `run_search`, also returning the exact bytes eBay sent, e.g. to archive or
+/// hash them. The raw body is read fresh from eBay; no cache is involved.
+pub async fn run_search_raw(config: SearchConfig) -> Result<(SearchResponse, Bytes), EbayError> {
+    EbayClient::new().search_raw(config).await
+}
+
+/// Same as `run_search`, parsing the response into any type instead of `SearchResponse`:
+/// `run_search_as::<MyResponse>(config)`. Useful for fields the crate doesn't model.
+pub async fn run_search_as<T: DeserializeOwned>(config: SearchConfig) -> Result<T, EbayError> {
+    EbayClient::new().search_as(config).await
+}
+
+/// Number of listings matching the query and filter, without fetching them: the search is
+/// sent with `limit=1` and only eBay's `total` is kept; see `EbayClient::count`
+pub async fn count(
+    query: impl Into<Value>,
+    filter: SearchFilter,
+    access_token: &str,
+    environment: Environment
+) -> Result<u64, EbayError> {
+    EbayClient::new()
+        .environment(environment)
+        .count(query, filter, access_token).await
+}
+
+/// Run the same query on several marketplaces concurrently, each result keyed by its
+/// marketplace; see `EbayClient::search_across`
+pub async fn search_across(
+    query: impl Into<Value>,
+    marketplaces: &[Marketplace],
+    access_token: &str,
+    environment: Environment
+) -> HashMap<Marketplace, Result<SearchResponse, EbayError>> {
+    EbayClient::new()
+        .environment(environment)
+        .search_across(query, marketplaces, access_token).await
+}
+
+/// Follow the response's `next` link to the following page, None on the last page.
+/// Safer than recomputing offsets, since eBay builds the link itself; see
+/// `EbayClient::fetch_next`.
+pub async fn fetch_next(
+    client: &EbayClient,
+    response: &SearchResponse,
+    access_token: &str
+) -> Result<Option<SearchResponse>, EbayError> {
+    client.fetch_next(response, access_token).await
+}
+
+/// Old name of `run_search`. Despite the name the search is sent as a GET.
+#[deprecated(note = "renamed to `run_search`; the request was always a GET, not a POST")]
+pub async fn post_query(config: SearchConfig) -> Result<SearchResponse, EbayError> {
+    run_search(config).await
+}
+
+/// Old name of `run_search_raw`
+#[deprecated(note = "renamed to `run_search_raw`")]
+pub async fn post_query_raw(config: SearchConfig) -> Result<(SearchResponse, Bytes), EbayError> {
+    run_search_raw(config).await
+}
+
+/// Old name of `run_search_as`
+#[deprecated(note = "renamed to `run_search_as`")]
+pub async fn post_query_as<T: DeserializeOwned>(config: SearchConfig) -> Result<T, EbayError> {
+    run_search_as(config).await
+}
+
+/// Search once with a prebuilt filter and the default settings otherwise:
+/// `search("laptop", SearchFilter::new().price_range(None, Some(500.0)), token, env)`.
+/// The query can be a `&str`, a `String` or a `Query`.
+pub async fn search(
+    query: impl Into<Value>,
+    filter: SearchFilter,
+    access_token: &str,
+    environment: Environment
+) -> Result<SearchResponse, EbayError> {
+    let config = SearchConfig::builder()
+        .query(query)
+        .filter(filter)
+        .access_token(access_token)
+        .environment(environment)
+        .build()?;
+
+    EbayClient::new().environment(environment).search(config).await
+}
+
+/// Search for the query within one category, e.g. `search_in_category("iphone", "9355", ..)`
+pub async fn search_in_category(
+    query: impl Into<Value>,
+    category_id: &str,
+    access_token: &str,
+    environment: Environment
+) -> Result<SearchResponse, EbayError> {
+    let config = SearchConfig::builder()
+        .query(query)
+        .category_ids(&[category_id])
+        .access_token(access_token)
+        .environment(environment)
+        .build()?;
+
+    EbayClient::new().environment(environment).search(config).await
+}
+
+/// Send a GET request and read its body, checking the status and that it holds JSON
+pub(crate) async fn send_get_bytes<P: serde::Serialize + ?Sized>(
+    client: &reqwest::Client,
+    interceptor: &dyn RequestInterceptor,
+    url: &str,
+    headers: &HeaderMap,
+    parameters: &P,
+    retries: u32
+) -> Result<Bytes, EbayError> {
+    let send = async {
+        send_get_response(client, interceptor, url, headers, parameters).await.map_err(EbayError::Http)
+    };
+
+    receive_bytes(url, retries, send).await
+}
+
+// Each request gets its own span carrying the url, the number of retries that led to it,
+// and once eBay answers, the status code and how long the round trip took
+#[tracing::instrument(
+    name = "ebay_request",
+    skip(url, send),
+    fields(url = %url, status, duration_ms)
+)]
+pub(crate) async fn receive_bytes(
+    url: &str,
+    retries: u32,
+    send: impl Future<Output = Result<reqwest::Response, EbayError>>
+) -> Result<Bytes, EbayError> {
+    let started = Instant::now();
+    let span = tracing::Span::current();
+
+    let response = send.await
+        .map_err(|err| {
+            span.record("duration_ms", started.elapsed().as_millis() as u64);
+            telemetry::record_request(None, started.elapsed());
+            tracing::warn!(error = %err, "request failed before eBay responded");
+            err
+        })?;
+
+    let status = response.status();
+    let response_headers = response.headers().clone();
+    span.record("status", status.as_u16());
+    let body = response.bytes().await?;
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    telemetry::record_request(Some(status.as_u16()), started.elapsed());
+
+    check_response(status, &response_headers, &body)?;
+    tracing::debug!("request succeeded");
+
+    Ok(body)
+}
+
+/// The error a response stands for: an error status, a body that isn't JSON, or errors
+/// sent with a success status. Shared by the async and blocking clients.
+pub(crate) fn check_response(
+    status: reqwest::StatusCode,
+    headers: &HeaderMap,
+    body: &[u8]
+) -> Result<(), EbayError> {
+    if !status.is_success() {
+        tracing::warn!("eBay returned an error status");
+        let body = String::from_utf8_lossy(body).into_owned();
+        return Err(EbayError::from_response(status, headers, body));
+    }
+
+    if let Some(err) = EbayError::from_non_json(status, headers, body) {
+        tracing::warn!("eBay returned a response that isn't JSON");
+        return Err(err);
+    }
+
+    if let Some(err) = EbayError::from_success_body(status, headers, body) {
+        tracing::warn!("eBay returned errors with a success status");
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Send a GET request without reading the response. Single-valued headers are collapsed
+/// to their first value and the interceptor runs last, just before sending.
+pub(crate) async fn send_get_response<P: serde::Serialize + ?Sized>(
+    client: &reqwest::Client,
+    interceptor: &dyn RequestInterceptor,
+    url: &str,
+    headers: &HeaderMap,
+    parameters: &P
+) -> Result<reqwest::Response, reqwest::Error> {
+    let mut headers = headers.clone();
+    for name in RESERVED_HEADERS {
+        keep_first_value(&mut headers, name);
+    }
+    keep_first_value(&mut headers, marketplace_header_name());
+
+    let request = client
+        .get(url)
+        .headers(headers)
+        .query(parameters);
+
+    interceptor.intercept(request).send().await
+}
+
+/// Same as `send_get_bytes`, parsing the body as JSON
+pub(crate) async fn send_get<P: serde::Serialize + ?Sized>(
+    client: &reqwest::Client,
+    interceptor: &dyn RequestInterceptor,
+    url: &str,
+    headers: &HeaderMap,
+    parameters: &P,
+    retries: u32
+) -> Result<Value, EbayError> {
+    let body = send_get_bytes(client, interceptor, url, headers, parameters, retries).await?;
+
+    serde_json::from_slice(&body).map_err(EbayError::Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sandbox_uses_the_sandbox_host() {
+        assert_eq!(Environment::Sandbox.api_host(), "api.sandbox.ebay.com");
+        assert_eq!(
+            Environment::Sandbox.search_url(),
+            "https://api.sandbox.ebay.com/buy/browse/v1/item_summary/search"
+        );
+    }
+
+    #[test]
+    fn production_never_points_at_the_sandbox() {
+        assert_eq!(Environment::Production.api_host(), "api.ebay.com");
+        for url in [
+            Environment::Production.base_url(),
+            Environment::Production.search_url(),
+            Environment::Production.item_url(),
+            Environment::Production.token_url(),
+        ] {
+            assert!(!url.contains("sandbox"), "{}", url);
+        }
+    }
+
+    #[test]
+    fn gtin_brand_and_mpn_pin_a_catalog_product() {
+        let config = SearchConfig::builder()
+            .gtin(" 0190199098428 ")
+            .category_ids(&["9355", "15032"])
+            .brand("Apple")
+            .mpn("MQ8L2LL/A")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.search_parameters["gtin"], "0190199098428");
+        assert_eq!(config.search_parameters["aspect_filter"], "categoryId:9355,Brand:{Apple},MPN:{MQ8L2LL/A}");
+        assert!(!config.search_parameters.contains_key("q"));
+    }
+
+    #[test]
+    fn gtin_alone_is_something_to_search_for() {
+        assert!(SearchConfig::builder().gtin("0190199098428").build().is_ok());
+
+        let err = SearchConfig::builder().build().unwrap_err();
+        assert!(err.to_string().contains("a query, category_ids, gtin or epid"), "{}", err);
+    }
+
+    #[test]
+    fn aspect_values_are_escaped() {
+        let config = SearchConfig::builder()
+            .category_ids(&["11450"])
+            .aspect("Style", "A,B|{C}")
+            .aspect("Path", "C:\\Temp\\")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config.search_parameters["aspect_filter"],
+            "categoryId:11450,Style:{A\\,B\\|\\{C\\}},Path:{C:\\\\Temp\\\\}"
+        );
+    }
+
+    #[test]
+    fn aspects_need_a_category() {
+        let err = SearchConfig::builder().query("phone").brand("Apple").build().unwrap_err();
+
+        assert!(err.to_string().contains("aspect_filter"), "{}", err);
+    }
+
+    #[test]
+    fn builder_has_no_credentials_by_default() {
+        let config = SearchConfig::builder().query("laptop").build().unwrap();
+        assert!(config.app_id.is_empty());
+        assert!(expose(&config.cert_id).is_empty());
+
+        let config = SearchConfig::builder()
+            .query("laptop")
+            .app_id("app-id")
+            .cert_id("cert-id")
+            .build()
+            .unwrap();
+        assert_eq!(config.app_id, "app-id");
+        assert_eq!(expose(&config.cert_id), "cert-id");
+    }
+
+    #[test]
+    fn debug_output_redacts_credentials() {
+        let manager = Arc::new(TokenManager::new("app-id-123", "cert-id-456"));
+        let builder = SearchConfig::builder()
+            .query("laptop")
+            .app_id("app-id-123")
+            .cert_id("cert-id-456")
+            .access_token("token-789")
+            .token_manager(manager);
+
+        let printed = [format!("{:?}", builder), format!("{:?}", builder.build().unwrap())];
+
+        for printed in printed {
+            for credential in ["app-id-123", "cert-id-456", "token-789"] {
+                assert!(!printed.contains(credential), "{} leaked in {}", credential, printed);
+            }
+            assert!(printed.contains("[REDACTED]"), "{}", printed);
+            assert!(printed.contains("laptop"), "{}", printed);
+        }
+    }
+
+    #[test]
+    fn locale_sets_the_language_headers() {
+        let config = SearchConfig::builder().query("laptop").locale("de-DE").build().unwrap();
+
+        assert_eq!(config.headers[header::ACCEPT_LANGUAGE], "de-DE");
+        assert_eq!(config.headers[header::CONTENT_LANGUAGE], "de-DE");
+    }
+
+    #[test]
+    fn invalid_locale_is_a_validation_error() {
+        let result = SearchConfig::builder().query("laptop").locale("de\nDE").build();
+
+        match result {
+            Err(EbayError::Validation(message)) => assert!(message.starts_with("accept-language:"), "{}", message),
+            other => panic!("expected a Validation error, got {:?}", other),
+        }
+    }
+
+    fn header_map(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(
+                header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                header::HeaderValue::from_str(value).unwrap()
+            );
+        }
+        headers
+    }
+
+    fn values<'a>(headers: &'a HeaderMap, name: &str) -> Vec<&'a str> {
+        headers
+            .get_all(name)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn default_headers_ask_for_json() {
+        let config = SearchConfig::builder().query("laptop").build().unwrap();
+
+        assert_eq!(values(&default_headers("token"), "accept"), ["application/json"]);
+        assert_eq!(values(&config.headers, "accept"), ["application/json"]);
+    }
+
+    #[test]
+    fn with_headers_replaces_existing_headers_and_keeps_every_value() {
+        let config = SearchConfig::new(json!("laptop"), String::from("token")).with_headers(
+            header_map(&[("accept-language", "fr-FR"), ("x-trace", "a"), ("x-trace", "b")])
+        );
+
+        assert_eq!(values(&config.headers, "accept-language"), ["fr-FR"]);
+        assert_eq!(values(&config.headers, "x-trace"), ["a", "b"]);
+    }
+
+    #[test]
+    fn with_headers_never_overrides_reserved_headers() {
+        let config = SearchConfig::new(json!("laptop"), String::from("token")).with_headers(
+            header_map(
+                &[
+                    ("authorization", "Bearer other"),
+                    ("authorization", "Basic abc"),
+                    ("content-type", "text/plain"),
+                    ("x-trace", "a"),
+                ]
+            )
+        );
+
+        assert_eq!(values(&config.headers, "authorization"), ["Bearer token"]);
+        assert_eq!(values(&config.headers, "content-type"), ["application/json"]);
+        assert_eq!(values(&config.headers, "x-trace"), ["a"]);
+    }
+
+    #[test]
+    fn merge_headers_keeps_values_after_a_skipped_reserved_header_apart() {
+        let mut headers = default_headers("token");
+        merge_headers(
+            &mut headers,
+            header_map(&[("x-first", "1"), ("authorization", "Bearer other"), ("x-second", "2")])
+        );
+
+        assert_eq!(values(&headers, "x-first"), ["1"]);
+        assert_eq!(values(&headers, "x-second"), ["2"]);
+        assert_eq!(values(&headers, "authorization"), ["Bearer token"]);
+    }
+
+    #[test]
+    fn keep_first_value_collapses_duplicates() {
+        let mut headers = header_map(&[("authorization", "Bearer first"), ("authorization", "Bearer second")]);
+
+        keep_first_value(&mut headers, header::AUTHORIZATION);
+
+        assert_eq!(values(&headers, "authorization"), ["Bearer first"]);
     }
 
-    #[tokio::main]
-    pub async fn post_query(config: SearchConfig) -> Result<(), reqwest::Error> {
-        // Make a GET request with the url from SearchConfig
+    #[test]
+    fn access_token_is_sent_verbatim() {
+        let config = SearchConfig::new(json!("laptop"), String::from("abc.def-123"));
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(config.search_url)
-            .headers(config.headers)
-            .query(&config.search_parameters)
-            .send().await?;
+        assert_eq!(config.headers[header::AUTHORIZATION], "Bearer abc.def-123");
+    }
 
-        if response.status().is_success() {
-            let body = response.text().await?;
-            let parsed_json: Value = serde_json::from_str(&body).expect("failed to parse json");
-            let pretty_json = serde_json
-                ::to_string_pretty(&parsed_json)
-                .expect("failed to pretty json");
+    #[test]
+    fn surrounding_whitespace_is_trimmed_from_the_token() {
+        for token in ["  abc.def-123", "abc.def-123\n", "\tabc.def-123 \r\n"] {
+            let config = SearchConfig::new(json!("laptop"), token.to_string());
 
-            println!("Response body: {}", pretty_json);
-        } else {
-            println!("Request failed with status code: {}", response.status());
+            assert_eq!(config.headers[header::AUTHORIZATION], "Bearer abc.def-123", "{:?}", token);
         }
 
-        Ok(())
+        let mut config = SearchConfig::new(json!("laptop"), String::from("old"));
+        config.set_access_token(" new-token\n");
+        assert_eq!(config.headers[header::AUTHORIZATION], "Bearer new-token");
+    }
+
+    #[test]
+    fn authorization_header_is_sensitive() {
+        assert!(bearer("abc.def-123").is_sensitive());
+    }
+
+    fn filter_param(config: &SearchConfig) -> Option<&str> {
+        config.search_parameters.get("filter")?.as_str()
+    }
+
+    #[test]
+    fn price_currency_is_inferred_from_the_marketplace() {
+        let config = SearchConfig::builder()
+            .query("laptop")
+            .marketplace(Marketplace::UnitedKingdom)
+            .filter(SearchFilter::new().price_range(Some(10.0), Some(50.0)))
+            .build()
+            .unwrap();
+
+        assert_eq!(filter_param(&config), Some("price:[10..50],priceCurrency:GBP"));
+    }
+
+    #[test]
+    fn explicit_price_currency_is_kept() {
+        let config = SearchConfig::builder()
+            .query("laptop")
+            .marketplace(Marketplace::UnitedKingdom)
+            .filter(SearchFilter::new().price_range(None, Some(50.0)).price_currency("USD"))
+            .build()
+            .unwrap();
+
+        assert_eq!(filter_param(&config), Some("price:[..50],priceCurrency:USD"));
+    }
+
+    #[test]
+    fn no_currency_without_a_price_range() {
+        let config = SearchConfig::builder()
+            .query("laptop")
+            .marketplace(Marketplace::UnitedKingdom)
+            .filter(SearchFilter::new().delivery_country("GB"))
+            .build()
+            .unwrap();
+
+        assert_eq!(filter_param(&config), Some("deliveryCountry:GB"));
+    }
+
+    #[test]
+    fn marketplace_header_and_defaults_agree() {
+        let cases = [
+            (Marketplace::Motors, "EBAY_MOTORS", "USD", "en-US", "US"),
+            (Marketplace::UnitedStates, "EBAY_US", "USD", "en-US", "US"),
+            (Marketplace::Germany, "EBAY_DE", "EUR", "de-DE", "DE"),
+        ];
+
+        for (marketplace, id, currency, locale, country) in cases {
+            let config = SearchConfig::builder()
+                .query("brake pads")
+                .marketplace(marketplace)
+                .filter(SearchFilter::new().price_range(Some(20.0), None))
+                .delivery_postal_code("12345")
+                .environment(Environment::Production)
+                .build()
+                .unwrap();
+
+            assert_eq!(config.headers[MARKETPLACE_HEADER], id);
+            assert_eq!(config.headers[header::ACCEPT_LANGUAGE], locale);
+            assert_eq!(
+                filter_param(&config).unwrap(),
+                format!(
+                    "price:[20],priceCurrency:{},deliveryCountry:{},deliveryPostalCode:12345",
+                    currency,
+                    country
+                )
+            );
+            // Every marketplace is served from the same host
+            assert_eq!(config.search_url, Environment::Production.search_url());
+        }
+    }
+
+    fn parameters(value: Value) -> serde_json::Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn query_string_is_sorted_by_key() {
+        let parameters = parameters(json!({ "q": "laptop", "limit": 5, "category_ids": "177", "offset": 0 }));
+
+        assert_eq!(query_string(&parameters), "category_ids=177&limit=5&offset=0&q=laptop");
+    }
+
+    #[test]
+    fn query_string_encodes_spaces_and_reserved_characters() {
+        let parameters = parameters(json!({ "q": "red shoes", "filter": "price:[10..50],conditionIds:{1000|3000}" }));
+
+        assert_eq!(
+            query_string(&parameters),
+            "filter=price%3A%5B10..50%5D%2CconditionIds%3A%7B1000%7C3000%7D&q=red%20shoes"
+        );
+        assert_eq!(percent_encode("a&b=c+d/e?f#g"), "a%26b%3Dc%2Bd%2Fe%3Ff%23g");
+    }
+
+    #[test]
+    fn query_string_encodes_unicode_as_utf8() {
+        let parameters = parameters(json!({ "q": "café €" }));
+
+        assert_eq!(query_string(&parameters), "q=caf%C3%A9%20%E2%82%AC");
+    }
+
+    #[test]
+    fn query_string_keeps_unreserved_characters_and_drops_json_quotes() {
+        let parameters = parameters(json!({ "q": "A-z_0.9~", "auto_correct": true }));
+
+        assert_eq!(query_string(&parameters), "auto_correct=true&q=A-z_0.9~");
+        assert_eq!(query_string(&serde_json::Map::new()), "");
     }
 }
@@ -0,0 +1,344 @@
+//! # Error Module
+//!
+//! The `error` module defines `EbayError`, the error type returned by every request made
+//! against the eBay API, along with `ApiErrorDetail` for the `errors` array eBay includes in
+//! failed responses.
+
+use std::fmt;
+use std::time::{ Duration, SystemTime };
+use reqwest::header::{ HeaderMap, CONTENT_TYPE, RETRY_AFTER };
+use reqwest::StatusCode;
+use serde::de::IgnoredAny;
+use serde_derive::Deserialize;
+
+use crate::models::ApiWarning;
+
+/// eBay error ID returned when the OAuth access token is invalid or has expired
+pub const INVALID_ACCESS_TOKEN: u32 = 1001;
+
+/// Characters of an unexpected non-JSON body kept in the error
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// Bytes of an error response body an `Api` error keeps unless configured otherwise,
+/// see `EbayClient::max_error_body`
+pub const DEFAULT_MAX_ERROR_BODY: usize = 2048;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A single entry of the `errors` array in an eBay error response
+pub struct ApiErrorDetail {
+    pub error_id: u32,
+    #[serde(default)]
+    pub domain: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub message: String,
+    pub long_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    errors: Vec<ApiErrorDetail>,
+}
+
+// Just enough of a successful response to tell whether it is really an error in disguise
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SuccessProbe {
+    #[serde(default)]
+    errors: Vec<ApiErrorDetail>,
+    item_summaries: Option<IgnoredAny>,
+    items: Option<IgnoredAny>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// One broken constraint found by `SearchConfig::validate`
+pub struct ValidationError {
+    /// Query parameter or header the problem is with, e.g. `limit`
+    pub parameter: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.parameter, self.message)
+    }
+}
+
+#[derive(Debug)]
+/// Errors that can occur while talking to the eBay API
+pub enum EbayError {
+    /// The request could not be sent or the response could not be read
+    Http(reqwest::Error),
+    /// eBay answered with a non-success status code.
+    /// `body` keeps the raw response for anything the parsed `errors` leave out, cut to
+    /// `DEFAULT_MAX_ERROR_BODY` bytes by the clients unless configured otherwise.
+    /// `retry_after` is how long eBay asked us to wait, from the `Retry-After` header.
+    Api {
+        status: StatusCode,
+        errors: Vec<ApiErrorDetail>,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+    /// The response body was not the JSON we expected
+    Json(serde_json::Error),
+    /// Requesting or refreshing an OAuth token failed
+    Token(String),
+    /// Required configuration is missing or invalid
+    Config(String),
+    /// A search setting breaks one of eBay's constraints, caught before sending
+    Validation(String),
+    /// eBay answered successfully but with warnings, and the client is in strict mode
+    Warnings(Vec<ApiWarning>),
+    /// A paginated search was stopped before finishing, e.g. after too many errors
+    Pagination(String),
+    /// A local file, such as the config file, could not be read or written
+    Io(std::io::Error),
+}
+
+impl EbayError {
+    /// Build an `Api` error from a status code, the response headers and the raw body,
+    /// parsing eBay's `errors` array when the body contains one
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: String) -> Self {
+        let errors = serde_json
+            ::from_str::<ErrorBody>(&body)
+            .map(|parsed| parsed.errors)
+            .unwrap_or_default();
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+
+        EbayError::Api { status, errors, body, retry_after }
+    }
+
+    /// Cut the raw body of an `Api` error to at most `max_len` bytes, marking the cut with
+    /// `...`, so a large HTML error page doesn't flood the logs. The status and the parsed
+    /// `errors` are kept as they are. Other variants are returned unchanged.
+    pub fn truncate_body(mut self, max_len: usize) -> Self {
+        if let EbayError::Api { body, .. } = &mut self {
+            if body.len() > max_len {
+                let mut end = max_len;
+                while !body.is_char_boundary(end) {
+                    end -= 1;
+                }
+                body.truncate(end);
+                body.push_str("...");
+            }
+        }
+
+        self
+    }
+
+    /// True for failures that may go away on their own: rate limiting and server errors
+    pub fn is_transient(&self) -> bool {
+        match self {
+            EbayError::Api { status, .. } =>
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+            _ => false,
+        }
+    }
+
+    /// True when the request never got a response because the connection failed or timed
+    /// out, e.g. a DNS failure, or a server that accepted the connection and then reset or
+    /// closed it before answering. Errors reading or decoding a response body don't count,
+    /// since eBay did answer.
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            // A dropped connection surfaces as a request error without a status
+            EbayError::Http(err) =>
+                err.is_connect() || err.is_timeout() || (err.is_request() && err.status().is_none()),
+            _ => false,
+        }
+    }
+
+    /// How long to wait before trying again, when eBay said so
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            EbayError::Api { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// eBay occasionally answers 200 with a body that is only an `errors` array and no
+    /// results. Returns that case as an `Api` error; None for a genuine success.
+    pub fn from_success_body(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        let probe: SuccessProbe = serde_json::from_slice(body).ok()?;
+
+        if probe.errors.is_empty() || probe.item_summaries.is_some() || probe.items.is_some() {
+            return None;
+        }
+
+        let body = String::from_utf8_lossy(body).into_owned();
+
+        Some(EbayError::from_response(status, headers, body))
+    }
+
+    /// A response whose `Content-Type` isn't JSON, e.g. the HTML of a captcha or error page
+    /// served by a proxy or firewall in front of eBay, as an `Api` error carrying the content
+    /// type and the start of the body. None when the content type is JSON or missing.
+    pub fn from_non_json(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        let content_type = headers.get(CONTENT_TYPE)?.to_str().unwrap_or_default();
+        let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+
+        if mime == "application/json" || mime.ends_with("+json") {
+            return None;
+        }
+
+        let text = String::from_utf8_lossy(body);
+        let mut snippet: String = text.chars().take(BODY_SNIPPET_CHARS).collect();
+        if text.chars().count() > BODY_SNIPPET_CHARS {
+            snippet.push_str("...");
+        }
+
+        let body = format!("expected JSON but got {}: {}", content_type, snippet.trim());
+
+        Some(EbayError::from_response(status, headers, body))
+    }
+
+    /// True when eBay rejected the request because the access token is invalid or expired
+    pub fn is_expired_token(&self) -> bool {
+        match self {
+            EbayError::Api { status, errors, .. } =>
+                *status == StatusCode::UNAUTHORIZED &&
+                    errors.iter().any(|error| error.error_id == INVALID_ACCESS_TOKEN),
+            _ => false,
+        }
+    }
+}
+
+/// Short explanation of what usually causes eBay to answer with this status code
+pub fn status_hint(status: StatusCode) -> Option<&'static str> {
+    match status.as_u16() {
+        400 => Some("bad request params"),
+        401 => Some("token invalid or expired"),
+        403 => Some("insufficient scope"),
+        404 => Some("resource not found"),
+        429 => Some("rate limited"),
+        500..=599 => Some("eBay server error, try again later"),
+        _ => None,
+    }
+}
+
+/// Parse a `Retry-After` value, either a number of seconds or an HTTP-date.
+/// A date in the past means no wait.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+
+    Some(date.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+}
+
+impl fmt::Display for EbayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EbayError::Http(err) => write!(f, "request failed: {}", err),
+            EbayError::Api { status, errors, body, retry_after } => {
+                write!(f, "eBay returned {}", status)?;
+
+                if let Some(hint) = status_hint(*status) {
+                    write!(f, " ({})", hint)?;
+                }
+
+                match errors.first() {
+                    Some(error) => write!(f, ": {} (errorId {})", error.message, error.error_id)?,
+                    None => write!(f, ": {}", body)?,
+                }
+
+                match retry_after {
+                    Some(delay) => write!(f, "; retry after {}s", delay.as_secs()),
+                    None => Ok(()),
+                }
+            }
+            EbayError::Json(err) => write!(f, "failed to parse response: {}", err),
+            EbayError::Token(message) => write!(f, "failed to obtain access token: {}", message),
+            EbayError::Config(message) => write!(f, "invalid configuration: {}", message),
+            EbayError::Validation(message) => write!(f, "invalid search: {}", message),
+            EbayError::Warnings(warnings) => {
+                let messages: Vec<String> = warnings
+                    .iter()
+                    .map(|warning| format!("{} (errorId {})", warning.message, warning.error_id))
+                    .collect();
+                write!(f, "eBay returned warnings: {}", messages.join("; "))
+            }
+            EbayError::Pagination(message) => write!(f, "pagination aborted: {}", message),
+            EbayError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for EbayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EbayError::Http(err) => Some(err),
+            EbayError::Json(err) => Some(err),
+            EbayError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for EbayError {
+    fn from(err: reqwest::Error) -> Self {
+        EbayError::Http(err)
+    }
+}
+
+impl From<serde_json::Error> for EbayError {
+    fn from(err: serde_json::Error) -> Self {
+        EbayError::Json(err)
+    }
+}
+
+impl From<std::io::Error> for EbayError {
+    fn from(err: std::io::Error) -> Self {
+        EbayError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERRORS_ONLY: &str = r#"{"errors":[{"errorId":12001,"domain":"API_BROWSE","category":"REQUEST","message":"The 'filter' value is invalid."}]}"#;
+
+    #[test]
+    fn errors_with_a_success_status_are_an_api_error() {
+        let err = EbayError::from_success_body(StatusCode::OK, &HeaderMap::new(), ERRORS_ONLY.as_bytes()).unwrap();
+
+        match err {
+            EbayError::Api { status, errors, body, .. } => {
+                assert_eq!(status, StatusCode::OK);
+                assert_eq!(errors[0].error_id, 12001);
+                assert_eq!(body, ERRORS_ONLY);
+            }
+            other => panic!("expected an Api error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn genuine_successes_are_left_alone() {
+        let bodies = [
+            r#"{"total":0}"#,
+            r#"{"total":1,"itemSummaries":[{"itemId":"v1|1|0"}]}"#,
+            r#"{"errors":[{"errorId":12001}],"itemSummaries":[]}"#,
+            r#"{"items":[]}"#,
+            "not json",
+        ];
+
+        for body in bodies {
+            assert!(
+                EbayError::from_success_body(StatusCode::OK, &HeaderMap::new(), body.as_bytes()).is_none(),
+                "{}",
+                body
+            );
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! # Fieldgroups Module
+//!
+//! The `fieldgroups` module models the `fieldgroups` search parameter, which selects which
+//! containers eBay includes in a search response, and checks combinations before they are sent.
+//! `ItemFieldGroup` covers the separate set of values the item endpoint accepts.
+//!
+//! ## Valid Combinations
+//!
+//! | Group                       | Combines with                              |
+//! |-----------------------------|--------------------------------------------|
+//! | `MATCHING_ITEMS`            | any group except `COMPACT` and `FULL`      |
+//! | `ASPECT_REFINEMENTS`        | any group except `COMPACT` and `FULL`      |
+//! | `BUYING_OPTION_REFINEMENTS` | any group except `COMPACT` and `FULL`      |
+//! | `CATEGORY_REFINEMENTS`      | any group except `COMPACT` and `FULL`      |
+//! | `CONDITION_REFINEMENTS`     | any group except `COMPACT` and `FULL`      |
+//! | `EXTENDED`                  | any group except `COMPACT` and `FULL`      |
+//! | `FULL`                      | nothing, it already includes every group   |
+//! | `COMPACT`                   | nothing, it trims the response to the minimum |
+//!
+//! At least one group must be given. eBay answers invalid combinations with an empty or
+//! partial response rather than an error, which is why they are rejected up front.
+
+use std::fmt;
+
+use crate::error::EbayError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// One value of the `fieldgroups` search parameter
+pub enum FieldGroup {
+    /// Item summaries, what eBay returns when no fieldgroups are given
+    MatchingItems,
+    AspectRefinements,
+    BuyingOptionRefinements,
+    CategoryRefinements,
+    ConditionRefinements,
+    /// Adds fields such as `shortDescription` to each item summary
+    Extended,
+    /// Item summaries plus every refinement container
+    Full,
+    /// The smallest response eBay can produce
+    Compact,
+}
+
+impl FieldGroup {
+    /// Value eBay uses for this group
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldGroup::MatchingItems => "MATCHING_ITEMS",
+            FieldGroup::AspectRefinements => "ASPECT_REFINEMENTS",
+            FieldGroup::BuyingOptionRefinements => "BUYING_OPTION_REFINEMENTS",
+            FieldGroup::CategoryRefinements => "CATEGORY_REFINEMENTS",
+            FieldGroup::ConditionRefinements => "CONDITION_REFINEMENTS",
+            FieldGroup::Extended => "EXTENDED",
+            FieldGroup::Full => "FULL",
+            FieldGroup::Compact => "COMPACT",
+        }
+    }
+
+    // Groups that can only be requested on their own
+    fn is_exclusive(&self) -> bool {
+        matches!(self, FieldGroup::Full | FieldGroup::Compact)
+    }
+}
+
+impl fmt::Display for FieldGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Check a combination of groups against the matrix above and render it as the
+/// comma separated `fieldgroups` value. Duplicates are dropped.
+pub fn render_fieldgroups(groups: &[FieldGroup]) -> Result<String, EbayError> {
+    let mut unique: Vec<FieldGroup> = Vec::new();
+    for group in groups {
+        if !unique.contains(group) {
+            unique.push(*group);
+        }
+    }
+
+    if unique.is_empty() {
+        return Err(EbayError::Validation(String::from("fieldgroups needs at least one group")));
+    }
+
+    if unique.len() > 1 {
+        if let Some(exclusive) = unique.iter().find(|group| group.is_exclusive()) {
+            let others: Vec<&str> = unique
+                .iter()
+                .filter(|group| *group != exclusive)
+                .map(|group| group.as_str())
+                .collect();
+
+            return Err(
+                EbayError::Validation(
+                    format!("fieldgroup {} can't be combined with {}", exclusive, others.join(", "))
+                )
+            );
+        }
+    }
+
+    let names: Vec<&str> = unique
+        .iter()
+        .map(|group| group.as_str())
+        .collect();
+
+    Ok(names.join(","))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// One value of the `fieldgroups` parameter of the get item endpoint
+pub enum ItemFieldGroup {
+    /// Adds the `product` block with catalog data: brand, MPNs, aspects and images
+    Product,
+    /// Only the fields that change often, such as price and availability
+    Compact,
+    /// Adds details about the seller
+    AdditionalSellerDetails,
+}
+
+impl ItemFieldGroup {
+    /// Value eBay uses for this group
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemFieldGroup::Product => "PRODUCT",
+            ItemFieldGroup::Compact => "COMPACT",
+            ItemFieldGroup::AdditionalSellerDetails => "ADDITIONAL_SELLER_DETAILS",
+        }
+    }
+}
+
+impl fmt::Display for ItemFieldGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,556 @@
+//! # Filter Module
+//!
+//! The `filter` module provides `SearchFilter`, a builder for the Browse API `filter` query
+//! parameter, together with the `Condition` and `BuyingOption` enums it filters on and the
+//! `SortOrder` results can be returned in.
+//!
+//! ## Grammar
+//!
+//! The rendered filter is a comma separated list of `field:value` pairs:
+//!
+//! - Ranges use brackets: `price:[10..50]`, `price:[10]` (at least 10), `price:[..50]`.
+//! - Sets of values use braces and pipes: `buyingOptions:{FIXED_PRICE|BEST_OFFER}`.
+//! - Single values are written as is: `deliveryCountry:US`.
+//!
+//! `escape_value` puts a backslash before the reserved characters in a value (`,`, `|`, `{`,
+//! `}` and the backslash itself), so a brand such as `A,B` is sent as `A\,B` instead of
+//! splitting the list. Aspect values from `SearchConfigBuilder::aspect` and
+//! `SearchResponse::refine_with` are always escaped.
+//!
+//! Empty settings are left out instead of rendering a field with nothing after the colon or
+//! an empty `{}` set, so the rendered string is always a well-formed list of fields.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use ebay_api_test::filter::{ BuyingOption, Condition, SearchFilter };
+//!
+//! let filter = SearchFilter::new()
+//!     .price_range(Some(10.0), Some(50.0))
+//!     .price_currency("USD")
+//!     .conditions(&[Condition::New, Condition::Used])
+//!     .buying_options(&[BuyingOption::FixedPrice]);
+//!
+//! assert_eq!(
+//!     filter.to_filter_string(),
+//!     "price:[10..50],priceCurrency:USD,conditionIds:{1000|3000},buyingOptions:{FIXED_PRICE}"
+//! );
+//! ```
+
+use std::fmt;
+use serde_derive::{ Deserialize, Serialize };
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Item condition, identified on eBay by a numeric condition ID
+pub enum Condition {
+    New,
+    OpenBox,
+    NewWithDefects,
+    CertifiedRefurbished,
+    ExcellentRefurbished,
+    VeryGoodRefurbished,
+    GoodRefurbished,
+    SellerRefurbished,
+    LikeNew,
+    Used,
+    VeryGood,
+    Good,
+    Acceptable,
+    ForParts,
+    /// A condition ID this crate doesn't know, kept as eBay sent it
+    Other(String),
+}
+
+impl Condition {
+    /// eBay's numeric condition ID
+    pub fn id(&self) -> String {
+        let id = match self {
+            Condition::New => 1000,
+            Condition::OpenBox => 1500,
+            Condition::NewWithDefects => 1750,
+            Condition::CertifiedRefurbished => 2000,
+            Condition::ExcellentRefurbished => 2010,
+            Condition::VeryGoodRefurbished => 2020,
+            Condition::GoodRefurbished => 2030,
+            Condition::SellerRefurbished => 2500,
+            Condition::LikeNew => 2750,
+            Condition::Used => 3000,
+            Condition::VeryGood => 4000,
+            Condition::Good => 5000,
+            Condition::Acceptable => 6000,
+            Condition::ForParts => 7000,
+            Condition::Other(id) => return id.clone(),
+        };
+
+        id.to_string()
+    }
+
+    /// Condition for an ID from a response, falling back to `Other` for unknown IDs
+    pub fn from_id(id: &str) -> Self {
+        match id.trim() {
+            "1000" => Condition::New,
+            "1500" => Condition::OpenBox,
+            "1750" => Condition::NewWithDefects,
+            "2000" => Condition::CertifiedRefurbished,
+            "2010" => Condition::ExcellentRefurbished,
+            "2020" => Condition::VeryGoodRefurbished,
+            "2030" => Condition::GoodRefurbished,
+            "2500" => Condition::SellerRefurbished,
+            "2750" => Condition::LikeNew,
+            "3000" => Condition::Used,
+            "4000" => Condition::VeryGood,
+            "5000" => Condition::Good,
+            "6000" => Condition::Acceptable,
+            "7000" => Condition::ForParts,
+            other => Condition::Other(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// How a listing can be bought
+pub enum BuyingOption {
+    FixedPrice,
+    Auction,
+    BestOffer,
+    ClassifiedAd,
+    /// A value this crate doesn't know yet, only produced when parsing responses
+    #[serde(other)]
+    Unknown,
+}
+
+impl BuyingOption {
+    /// Value eBay uses for this option
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuyingOption::FixedPrice => "FIXED_PRICE",
+            BuyingOption::Auction => "AUCTION",
+            BuyingOption::BestOffer => "BEST_OFFER",
+            BuyingOption::ClassifiedAd => "CLASSIFIED_AD",
+            BuyingOption::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for BuyingOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Order eBay returns search results in, the `sort` query parameter.
+/// Without one, results come in eBay's best match order.
+pub enum SortOrder {
+    /// Lowest price plus shipping first
+    PriceAscending,
+    /// Highest price plus shipping first
+    PriceDescending,
+    /// Closest to the buyer's postal code first, needs `pickupPostalCode` in the filter
+    Distance,
+    NewlyListed,
+    EndingSoonest,
+}
+
+impl SortOrder {
+    /// Value eBay uses for this order
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::PriceAscending => "price",
+            SortOrder::PriceDescending => "-price",
+            SortOrder::Distance => "distance",
+            SortOrder::NewlyListed => "newlyListed",
+            SortOrder::EndingSoonest => "endingSoonest",
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// Builder for the `filter` query parameter
+pub struct SearchFilter {
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub price_currency: Option<String>,
+    pub conditions: Vec<Condition>,
+    pub buying_options: Vec<BuyingOption>,
+    pub delivery_country: Option<String>,
+    pub delivery_postal_code: Option<String>,
+    pub extra: Vec<(String, String)>,
+}
+
+impl SearchFilter {
+    /// Create an empty filter
+    pub fn new() -> Self {
+        SearchFilter::default()
+    }
+
+    /// Only listings priced within the range; leave a bound as None to keep it open
+    pub fn price_range(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min_price = min;
+        self.max_price = max;
+        self
+    }
+
+    /// Currency the price range is expressed in, e.g. `USD`. When a price range is set
+    /// without one, `SearchConfigBuilder` uses the marketplace's currency.
+    pub fn price_currency(mut self, currency: &str) -> Self {
+        self.price_currency = Some(currency.to_string());
+        self
+    }
+
+    /// Only listings in one of these conditions
+    pub fn conditions(mut self, conditions: &[Condition]) -> Self {
+        self.conditions = conditions.to_vec();
+        self
+    }
+
+    /// Only listings offering one of these ways to buy
+    pub fn buying_options(mut self, options: &[BuyingOption]) -> Self {
+        self.buying_options = options.to_vec();
+        self
+    }
+
+    /// Two letter country code the item would be shipped to, e.g. `US`
+    pub fn delivery_country(mut self, country: &str) -> Self {
+        self.delivery_country = Some(country.to_string());
+        self
+    }
+
+    /// Postal code the item would be shipped to. eBay only honors it together
+    /// with `delivery_country`.
+    pub fn delivery_postal_code(mut self, postal_code: &str) -> Self {
+        self.delivery_postal_code = Some(postal_code.to_string());
+        self
+    }
+
+    /// Any other filter field, rendered as `field:value`. The value is passed through as
+    /// written, so it must already follow the grammar, e.g. `{A|B}` for a set. A field or
+    /// value that is empty after trimming is ignored rather than rendered as `field:`.
+    pub fn field(mut self, field: &str, value: &str) -> Self {
+        let (field, value) = (field.trim(), value.trim());
+
+        if !field.is_empty() && !value.is_empty() {
+            self.extra.push((field.to_string(), value.to_string()));
+        }
+        self
+    }
+
+    /// True when no filter has been set
+    pub fn is_empty(&self) -> bool {
+        self.to_filter_string().is_empty()
+    }
+
+    /// The rendered value of the `filter` parameter
+    pub fn to_filter_string(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+
+        if self.min_price.is_some() || self.max_price.is_some() {
+            let min = self.min_price.map(|price| price.to_string()).unwrap_or_default();
+            let max = self.max_price.map(|price| price.to_string());
+
+            match max {
+                Some(max) => parts.push(format!("price:[{}..{}]", min, max)),
+                None => parts.push(format!("price:[{}]", min)),
+            }
+        }
+
+        if let Some(currency) = self.price_currency.as_deref().filter(|currency| !currency.trim().is_empty()) {
+            parts.push(format!("priceCurrency:{}", currency.trim()));
+        }
+
+        let ids: Vec<String> = self.conditions
+            .iter()
+            .map(|condition| condition.id())
+            .filter(|id| !id.trim().is_empty())
+            .collect();
+        if !ids.is_empty() {
+            parts.push(format!("conditionIds:{{{}}}", ids.join("|")));
+        }
+
+        let options: Vec<&str> = self.buying_options
+            .iter()
+            .filter(|option| **option != BuyingOption::Unknown)
+            .map(|option| option.as_str())
+            .collect();
+        if !options.is_empty() {
+            parts.push(format!("buyingOptions:{{{}}}", options.join("|")));
+        }
+
+        if let Some(country) = self.delivery_country.as_deref().filter(|country| !country.trim().is_empty()) {
+            parts.push(format!("deliveryCountry:{}", country.trim()));
+        }
+
+        if let Some(postal_code) = self.delivery_postal_code.as_deref().filter(|code| !code.trim().is_empty()) {
+            parts.push(format!("deliveryPostalCode:{}", postal_code.trim()));
+        }
+
+        for (field, value) in &self.extra {
+            parts.push(format!("{}:{}", field, value));
+        }
+
+        parts.join(",")
+    }
+}
+
+/// Escape the characters of the filter grammar in a value with a backslash, so it is read
+/// as one value: `escape_value("A,B")` is `A\,B`
+pub fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if matches!(c, ',' | '|' | '{' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// One `aspect_filter` entry, `Name:{Value}`, with the value escaped
+pub fn aspect_entry(name: &str, value: &str) -> String {
+    format!("{}:{{{}}}", name.trim(), escape_value(value.trim()))
+}
+
+impl fmt::Display for SearchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_filter_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reserved characters, alone and mixed, that a value must survive
+    const VALUES: &[&str] = &["plain", "A,B", "A|B", "{A}", "C:\\Temp", "\\", ",|{}\\", "a\\,b"];
+
+    // Split on separators that aren't escaped, keeping the escapes in the pieces
+    fn split_unescaped(rendered: &str, separator: char) -> Vec<String> {
+        let mut pieces = vec![String::new()];
+        let mut chars = rendered.chars();
+
+        while let Some(c) = chars.next() {
+            let piece = pieces.last_mut().unwrap();
+            if c == '\\' {
+                piece.push(c);
+                piece.extend(chars.next());
+            } else if c == separator {
+                pieces.push(String::new());
+            } else {
+                piece.push(c);
+            }
+        }
+
+        pieces
+    }
+
+    fn unescape(value: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = value.chars();
+
+        while let Some(c) = chars.next() {
+            unescaped.extend(if c == '\\' { chars.next() } else { Some(c) });
+        }
+
+        unescaped
+    }
+
+    #[test]
+    fn escaped_values_round_trip_through_a_set() {
+        for value in VALUES {
+            let set = format!("{{{}|{}}}", escape_value(value), escape_value("Other"));
+            let rendered = SearchFilter::new().delivery_country("US").field("brand", &set).to_filter_string();
+
+            let fields = split_unescaped(&rendered, ',');
+            assert_eq!(fields.len(), 2, "{:?} rendered as {}", value, rendered);
+            assert_eq!(fields[0], "deliveryCountry:US");
+
+            let set = fields[1].strip_prefix("brand:{").and_then(|set| set.strip_suffix('}')).unwrap();
+            let values: Vec<String> = split_unescaped(set, '|').iter().map(|value| unescape(value)).collect();
+            assert_eq!(values, [value.to_string(), String::from("Other")], "{:?} rendered as {}", value, rendered);
+        }
+    }
+
+    #[test]
+    fn escaped_values_round_trip_through_an_aspect_entry() {
+        for value in VALUES {
+            let entry = aspect_entry("Brand", value);
+
+            let escaped = entry.strip_prefix("Brand:{").and_then(|entry| entry.strip_suffix('}')).unwrap();
+            assert_eq!(split_unescaped(escaped, ',').len(), 1, "{}", entry);
+            assert_eq!(split_unescaped(escaped, '|').len(), 1, "{}", entry);
+            assert_eq!(unescape(escaped), *value, "{}", entry);
+        }
+    }
+
+    // Small xorshift generator, seeded so a failing case can be replayed
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+
+        fn chance(&mut self) -> bool {
+            self.next().is_multiple_of(2)
+        }
+
+        // A distinct, non-empty selection from the choices, in a random order
+        fn pick<T: Clone + PartialEq>(&mut self, choices: &[T]) -> Vec<T> {
+            let mut picked = Vec::new();
+            for _ in 0..self.below(choices.len()) + 1 {
+                let choice = choices[self.below(choices.len())].clone();
+                if !picked.contains(&choice) {
+                    picked.push(choice);
+                }
+            }
+            picked
+        }
+
+        fn price(&mut self) -> Option<f64> {
+            self.chance().then(|| self.below(100_000) as f64 / 100.0)
+        }
+
+        fn value(&mut self) -> String {
+            const ALPHABET: &[char] = &['a', 'Z', '7', ' ', '|', '{', '}', ',', ':', '\\'];
+            (0..self.below(8) + 1).map(|_| ALPHABET[self.below(ALPHABET.len())]).collect()
+        }
+    }
+
+    const CONDITIONS: &[Condition] = &[
+        Condition::New,
+        Condition::OpenBox,
+        Condition::CertifiedRefurbished,
+        Condition::SellerRefurbished,
+        Condition::Used,
+        Condition::ForParts,
+    ];
+
+    const BUYING_OPTIONS: &[BuyingOption] = &[
+        BuyingOption::FixedPrice,
+        BuyingOption::Auction,
+        BuyingOption::BestOffer,
+        BuyingOption::ClassifiedAd,
+    ];
+
+    // The items of a `{A|B}` set, unescaped
+    fn set_items(value: &str) -> Vec<String> {
+        let inner = value.strip_prefix('{').and_then(|value| value.strip_suffix('}')).unwrap();
+        split_unescaped(inner, '|').iter().map(|item| unescape(item)).collect()
+    }
+
+    #[test]
+    fn generated_filters_are_well_formed_and_round_trip() {
+        // proptest isn't a dependency of this crate, so the cases come from a seeded generator
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for case in 0..2000 {
+            let conditions = if rng.chance() { rng.pick(CONDITIONS) } else { Vec::new() };
+            let options = if rng.chance() { rng.pick(BUYING_OPTIONS) } else { Vec::new() };
+            let (min, max) = (rng.price(), rng.price());
+            let brands: Vec<String> = match rng.chance() {
+                true => (0..rng.below(3) + 1).map(|_| rng.value()).collect(),
+                false => Vec::new(),
+            };
+
+            let mut filter = SearchFilter::new()
+                .price_range(min, max)
+                .conditions(&conditions)
+                .buying_options(&options);
+            if min.is_some() || max.is_some() {
+                filter = filter.price_currency("USD");
+            }
+            if !brands.is_empty() {
+                let escaped: Vec<String> = brands.iter().map(|brand| escape_value(brand)).collect();
+                filter = filter.field("brand", &format!("{{{}}}", escaped.join("|")));
+            }
+            let rendered = filter.to_filter_string();
+            let context = format!("case {}: {}", case, rendered);
+
+            let fields = match rendered.is_empty() {
+                true => Vec::new(),
+                false => split_unescaped(&rendered, ','),
+            };
+
+            let mut seen = Vec::new();
+            for field in fields {
+                let (name, value) = field.split_once(':').unwrap_or_else(|| panic!("no colon in {}", context));
+                assert!(!name.is_empty() && !value.is_empty(), "{}", context);
+                assert!(!seen.contains(&name.to_string()), "{} twice in {}", name, context);
+                seen.push(name.to_string());
+
+                match name {
+                    "price" => {
+                        let range = value.strip_prefix('[').and_then(|value| value.strip_suffix(']')).unwrap();
+                        let (low, high) = range.split_once("..").unwrap_or((range, ""));
+                        assert_eq!(low.parse::<f64>().ok(), min, "{}", context);
+                        assert_eq!(high.parse::<f64>().ok(), max, "{}", context);
+                    }
+                    "priceCurrency" => assert_eq!(value, "USD"),
+                    "conditionIds" => {
+                        let parsed: Vec<Condition> = set_items(value).iter().map(|id| Condition::from_id(id)).collect();
+                        assert_eq!(parsed, conditions, "{}", context);
+                    }
+                    "buyingOptions" => {
+                        let parsed: Vec<String> = options.iter().map(|option| option.as_str().to_string()).collect();
+                        assert_eq!(set_items(value), parsed, "{}", context);
+                    }
+                    "brand" => assert_eq!(set_items(value), brands, "{}", context),
+                    other => panic!("unexpected field {} in {}", other, context),
+                }
+            }
+
+            // Price and currency come as a pair
+            let has_price = min.is_some() || max.is_some();
+            let expected = 2 * usize::from(has_price) +
+                usize::from(!conditions.is_empty()) +
+                usize::from(!options.is_empty()) +
+                usize::from(!brands.is_empty());
+            assert_eq!(seen.len(), expected, "{}", context);
+        }
+    }
+
+    #[test]
+    fn empty_values_are_left_out() {
+        let cases = [
+            SearchFilter::new().price_currency(""),
+            SearchFilter::new().price_currency("  "),
+            SearchFilter::new().conditions(&[Condition::Other(String::new())]),
+            SearchFilter::new().conditions(&[]),
+            SearchFilter::new().buying_options(&[BuyingOption::Unknown]),
+            SearchFilter::new().delivery_country(" ").delivery_postal_code(""),
+            SearchFilter::new().field("", "{A}").field("brand", " "),
+        ];
+
+        for filter in cases {
+            assert_eq!(filter.to_filter_string(), "", "{:?}", filter);
+            assert!(filter.is_empty());
+        }
+    }
+
+    #[test]
+    fn empty_values_leave_the_other_fields_well_formed() {
+        let filter = SearchFilter::new()
+            .conditions(&[Condition::Other(String::new()), Condition::New])
+            .price_currency("")
+            .field("brand", "")
+            .delivery_country("US");
+
+        assert_eq!(filter.to_filter_string(), "conditionIds:{1000},deliveryCountry:US");
+    }
+}
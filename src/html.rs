@@ -0,0 +1,136 @@
+//! # HTML Module
+//!
+//! The `html` module turns the HTML of a listing description into plain text for comparing
+//! listings, enabled by the `html` feature. It is a small tag stripper rather than an HTML
+//! parser: good enough for descriptions, not for rendering pages.
+//!
+//! - Tags are removed; `<script>` and `<style>` are removed together with their content.
+//! - Line breaks, paragraphs, list items, table rows and headings end a line.
+//! - Common entities (`&amp;`, `&nbsp;`, `&#39;`, `&#x27;`, ...) are decoded.
+//! - Runs of whitespace collapse to one space, and blank lines are dropped.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use ebay_api_test::html::strip_html;
+//!
+//! let text = strip_html("<p>Great <b>laptop</b></p><p>Battery &amp; charger included</p>");
+//! assert_eq!(text, "Great laptop\nBattery & charger included");
+//! ```
+
+// Tags after which the text continues on a new line
+const LINE_BREAK_TAGS: &[&str] = &[
+    "br", "p", "div", "li", "tr", "h1", "h2", "h3", "h4", "h5", "h6", "ul", "ol", "table",
+];
+
+// Tags whose content is never text
+const SKIPPED_TAGS: &[&str] = &["script", "style"];
+
+/// Plain text of an HTML fragment, one line per paragraph, list item or `<br>`
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+
+        let Some(end) = rest[start..].find('>') else {
+            // An unclosed `<` is text, not a tag
+            text.push_str(&decode_entities(&rest[start..]));
+            rest = "";
+            break;
+        };
+
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let name = tag_name(tag);
+        if !tag.starts_with('/') && SKIPPED_TAGS.contains(&name.as_str()) {
+            let closing = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&closing) {
+                Some(close) => {
+                    let after = &rest[close..];
+                    after.find('>').map(|end| &after[end + 1..]).unwrap_or("")
+                }
+                None => "",
+            };
+            continue;
+        }
+
+        if LINE_BREAK_TAGS.contains(&name.as_str()) {
+            text.push('\n');
+        } else if name == "td" || name == "th" {
+            // Cells of one row stay on a line but apart; inline tags such as `<b>` add nothing
+            text.push(' ');
+        }
+    }
+    text.push_str(&decode_entities(rest));
+
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Lowercase name of the tag between `<` and `>`, without a leading `/` or attributes
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end + 1]).map(|c| (c, end + 2)));
+
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+// Character for an entity name such as `amp` or `#39`, None for unknown ones
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(code) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        return u32::from_str_radix(code, 16).ok().and_then(char::from_u32);
+    }
+    if let Some(code) = name.strip_prefix('#') {
+        return code.parse().ok().and_then(char::from_u32);
+    }
+
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => None,
+    }
+}
@@ -0,0 +1,195 @@
+//! # Ingest Module
+//!
+//! The `ingest` module turns the client into a batch enrichment step:
+//! `EbayClient::ingest_ids` reads item IDs from any `AsyncRead`, looks them up through the
+//! `item_ids` endpoint in batches of 20 and writes every item found as one line of JSON to
+//! an `AsyncWrite`.
+//!
+//! ## Input
+//!
+//! One ID per line, in any of these forms (blank lines are skipped):
+//!
+//! - a plain RESTful item ID: `v1|110551991234|0`
+//! - a JSON string: `"v1|110551991234|0"`
+//! - a JSON object with an `itemId` field, such as an item summary saved from a search:
+//!   `{"itemId": "v1|110551991234|0", ...}`
+//!
+//! ## Backpressure
+//!
+//! Lines are read only as batches are sent, and at most `concurrency` batches are in flight
+//! or waiting to be written at any time, so memory stays bounded however large the input is.
+//! The client's request limiter still applies on top.
+//!
+//! ## Example Usage
+//!
+//! ```no_run
+//! use ebay_api_test::client::EbayClient;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), ebay_api_test::error::EbayError> {
+//!     let client = EbayClient::new();
+//!     let access_token = "Your-OAuth-Access-Token";
+//!
+//!     let input = tokio::fs::File::open("item_ids.ndjson").await?;
+//!     let output = tokio::fs::File::create("items.ndjson").await?;
+//!
+//!     let summary = client.ingest_ids(input, output, access_token, 4).await?;
+//!     println!("{} of {} items found", summary.items_written, summary.ids_read);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use futures::stream::{ self, StreamExt };
+use serde_json::Value;
+use tokio::io::{ AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader };
+
+use crate::client::EbayClient;
+use crate::error::EbayError;
+use crate::item::MAX_ITEM_IDS_PER_REQUEST;
+use crate::models::Item;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Counts from a finished `ingest_ids` run
+pub struct IngestSummary {
+    /// IDs read from the input
+    pub ids_read: usize,
+    /// Items written to the output. IDs eBay has no listing for are left out, so this can
+    /// be lower than `ids_read`.
+    pub items_written: usize,
+}
+
+impl EbayClient {
+    /// Read item IDs from `input`, fetch their details in batches of 20 with at most
+    /// `concurrency` batches at a time, and write each item as a line of JSON to `output`.
+    /// Batches are written in input order, each in the order eBay returns its items.
+    /// Stops at the first error, after writing every batch before it; the output is
+    /// flushed on success.
+    pub async fn ingest_ids<R, W>(
+        &self,
+        input: R,
+        mut output: W,
+        access_token: &str,
+        concurrency: usize
+    ) -> Result<IngestSummary, EbayError>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let lines = BufReader::new(input).lines();
+
+        let ids = stream::unfold((lines, 0usize), |(mut lines, mut line_number)| async move {
+            loop {
+                line_number += 1;
+
+                let line = match lines.next_line().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => return None,
+                    Err(err) => return Some((Err(EbayError::from(err)), (lines, line_number))),
+                };
+
+                if let Some(id) = parse_id(&line, line_number).transpose() {
+                    return Some((id, (lines, line_number)));
+                }
+            }
+        });
+
+        let batches = ids
+            .chunks(MAX_ITEM_IDS_PER_REQUEST)
+            .map(|batch| async move {
+                let batch = batch.into_iter().collect::<Result<Vec<String>, EbayError>>()?;
+                let items = self.get_items(&batch, access_token).await?;
+
+                Ok::<(usize, Vec<Item>), EbayError>((batch.len(), items))
+            })
+            .buffered(concurrency.max(1));
+        futures::pin_mut!(batches);
+
+        let mut summary = IngestSummary::default();
+
+        while let Some(batch) = batches.next().await {
+            let (ids_read, items) = batch?;
+            summary.ids_read += ids_read;
+
+            for item in items {
+                let mut line = serde_json::to_vec(&item)?;
+                line.push(b'\n');
+                output.write_all(&line).await?;
+                summary.items_written += 1;
+            }
+        }
+
+        output.flush().await?;
+
+        Ok(summary)
+    }
+}
+
+// The ID on one input line, None for a blank line
+fn parse_id(line: &str, line_number: usize) -> Result<Option<String>, EbayError> {
+    let line = line.trim();
+
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    if !line.starts_with('{') && !line.starts_with('"') {
+        return Ok(Some(line.to_string()));
+    }
+
+    let id = match serde_json::from_str::<Value>(line)? {
+        Value::String(id) => Some(id),
+        Value::Object(object) =>
+            object
+                .get("itemId")
+                .and_then(|id| id.as_str())
+                .map(|id| id.to_string()),
+        _ => None,
+    };
+
+    match id.filter(|id| !id.trim().is_empty()) {
+        Some(id) => Ok(Some(id.trim().to_string())),
+        None => Err(EbayError::Validation(format!("line {}: no item ID found", line_number))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_id_is_read_as_is() {
+        assert_eq!(parse_id("  v1|110551991234|0 ", 1).unwrap(), Some("v1|110551991234|0".to_string()));
+    }
+
+    #[test]
+    fn json_string_is_unquoted() {
+        assert_eq!(parse_id(r#""v1|110551991234|0""#, 1).unwrap(), Some("v1|110551991234|0".to_string()));
+    }
+
+    #[test]
+    fn item_id_is_taken_from_an_object() {
+        let line = r#"{"itemId": "v1|110551991234|0", "title": "Laptop"}"#;
+
+        assert_eq!(parse_id(line, 1).unwrap(), Some("v1|110551991234|0".to_string()));
+    }
+
+    #[test]
+    fn blank_lines_are_skipped() {
+        assert_eq!(parse_id("", 1).unwrap(), None);
+        assert_eq!(parse_id(" \t", 2).unwrap(), None);
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        assert!(matches!(parse_id(r#"{"itemId": "#, 3), Err(EbayError::Json(_))));
+    }
+
+    #[test]
+    fn json_without_an_id_names_the_line() {
+        let err = parse_id(r#"{"title": "Laptop"}"#, 7).unwrap_err();
+
+        assert!(matches!(&err, EbayError::Validation(message) if message.starts_with("line 7")));
+        assert!(matches!(parse_id(r#"{"itemId": " "}"#, 8), Err(EbayError::Validation(_))));
+    }
+}
@@ -0,0 +1,56 @@
+//! # Interceptor Module
+//!
+//! The `interceptor` module defines `RequestInterceptor`, a hook `EbayClient` runs on every
+//! request just before sending it. It is meant for schemes the crate doesn't know about,
+//! such as a gateway that expects its own HMAC signature header.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use ebay_api_test::client::EbayClient;
+//! use ebay_api_test::interceptor::RequestInterceptor;
+//!
+//! #[derive(Debug)]
+//! struct GatewaySigner {
+//!     key: String,
+//! }
+//!
+//! impl GatewaySigner {
+//!     // Stand-in for the gateway's real signature scheme
+//!     fn sign(&self) -> String {
+//!         format!("hmac-{}", self.key.len())
+//!     }
+//! }
+//!
+//! impl RequestInterceptor for GatewaySigner {
+//!     fn intercept(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+//!         request.header("X-Gateway-Signature", self.sign())
+//!     }
+//! }
+//!
+//! let signer = GatewaySigner { key: String::from("Your-Gateway-Key") };
+//! let client = EbayClient::new().interceptor(Arc::new(signer));
+//! ```
+
+use std::fmt;
+
+/// Hook run on every request `EbayClient` sends, after the crate has set the URL, its
+/// headers and query parameters. Whatever the returned builder describes is sent.
+///
+/// Token requests are the exception: a `TokenManager` talks to eBay's OAuth endpoint
+/// through its own HTTP client, without the hook. The `BlockingClient` doesn't run it
+/// either.
+pub trait RequestInterceptor: fmt::Debug + Send + Sync {
+    fn intercept(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Interceptor that sends every request unchanged, the client's default
+pub struct NoopInterceptor;
+
+impl RequestInterceptor for NoopInterceptor {
+    fn intercept(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+    }
+}
@@ -0,0 +1,342 @@
+//! # Item Module
+//!
+//! The `item` module looks up listing details through the Browse API item endpoints.
+//!
+//! ## Example Usage
+//!
+//! ```no_run
+//! use ebay_api_test::ebay_api::Environment;
+//! use ebay_api_test::item::get_items;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let ids = vec![String::from("v1|110551991234|0"), String::from("v1|110551995678|0")];
+//!
+//!     // One request per 20 IDs instead of one request per ID
+//!     let items = get_items(&ids, "Your-OAuth-Access-Token", Environment::Sandbox).await;
+//! }
+//! ```
+
+use futures::future::{ try_join3, try_join_all };
+use reqwest::header::{ self, HeaderMap };
+use serde::Serialize;
+use serde_derive::Deserialize;
+use serde_json::Value;
+
+use crate::client::EbayClient;
+use crate::ebay_api::{ bearer, default_headers, merge_headers, Environment, ITEM_PATH };
+use crate::error::EbayError;
+use crate::fieldgroups::ItemFieldGroup;
+use crate::models::{ Item, SearchResponse };
+use crate::telemetry;
+
+/// Most item IDs the `item_ids` lookup accepts in a single request
+pub const MAX_ITEM_IDS_PER_REQUEST: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ItemsResponse {
+    #[serde(default)]
+    pub(crate) items: Vec<Item>,
+}
+
+/// Fetch full details for one listing by its RESTful item ID (`v1|<legacy id>|<variation id>`)
+pub async fn get_item(
+    id: &str,
+    access_token: &str,
+    environment: Environment
+) -> Result<Item, EbayError> {
+    EbayClient::new().environment(environment).get_item(id, access_token).await
+}
+
+/// Same as `get_item`, asking for extra fieldgroups, e.g. `&[ItemFieldGroup::Product]` to
+/// get the catalog `product` block
+pub async fn get_item_with_fieldgroups(
+    id: &str,
+    fieldgroups: &[ItemFieldGroup],
+    access_token: &str,
+    environment: Environment
+) -> Result<Item, EbayError> {
+    EbayClient::new()
+        .environment(environment)
+        .get_item_with_fieldgroups(id, fieldgroups, access_token).await
+}
+
+/// Fetch full details for many listings, batching IDs into groups of 20 per request and
+/// sending the batches concurrently. Items come back in the order eBay returns them for
+/// each batch, batch after batch.
+pub async fn get_items(
+    ids: &[String],
+    access_token: &str,
+    environment: Environment
+) -> Result<Vec<Item>, EbayError> {
+    EbayClient::new().environment(environment).get_items(ids, access_token).await
+}
+
+/// Fetch full details for a listing by its legacy item ID (the plain number used by the
+/// Finding and Trading APIs and in old listing URLs), e.g. `110551991234`
+pub async fn get_item_by_legacy_id(
+    legacy_id: &str,
+    access_token: &str,
+    environment: Environment
+) -> Result<Item, EbayError> {
+    EbayClient::new().environment(environment).get_item_by_legacy_id(legacy_id, access_token).await
+}
+
+/// Fetch every variation of a multi-variation listing by its item group ID, e.g. `151845160231`
+pub async fn get_items_by_item_group(
+    item_group_id: &str,
+    access_token: &str,
+    environment: Environment
+) -> Result<Vec<Item>, EbayError> {
+    EbayClient::new().environment(environment).get_items_by_item_group(item_group_id, access_token).await
+}
+
+/// Fetch full details for a mix of item IDs, legacy item IDs and item group IDs, looking up
+/// each kind on its own endpoint concurrently. Items for the item IDs come first, then the
+/// legacy IDs' items and finally the variations of each group, each in the order given.
+pub async fn get_items_and_groups(
+    ids: &[ItemLookup],
+    access_token: &str,
+    environment: Environment
+) -> Result<Vec<Item>, EbayError> {
+    EbayClient::new().environment(environment).get_items_and_groups(ids, access_token).await
+}
+
+/// Full details for every item in a page of search results, fetched 20 at a time with the
+/// batches sent concurrently. Uses the client's access token; see `EbayClient::enrich`.
+pub async fn enrich(client: &EbayClient, response: &SearchResponse) -> Result<Vec<Item>, EbayError> {
+    client.enrich(response).await
+}
+
+// Default headers plus any extra headers for a single call
+fn request_headers(access_token: &str, extra: Option<&HeaderMap>) -> HeaderMap {
+    let mut headers = default_headers(access_token);
+    if let Some(extra) = extra {
+        merge_headers(&mut headers, extra.clone());
+    }
+    headers
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// One listing or listing group to fetch with `get_items_and_groups`. Legacy item IDs and item
+/// group IDs are both plain numbers, so the kind can't be told from the ID and is given here.
+pub enum ItemLookup {
+    /// RESTful item ID, `v1|<legacy id>|<variation id>`
+    Item(String),
+    /// Legacy item ID used by the Finding and Trading APIs, e.g. `110551991234`
+    Legacy(String),
+    /// Item group ID of a multi-variation listing, fetching every variation, e.g. `151845160231`
+    Group(String),
+}
+
+impl EbayClient {
+    /// Fetch full details for one listing, see `get_item`
+    pub async fn get_item(&self, id: &str, access_token: &str) -> Result<Item, EbayError> {
+        self.get_item_with_fieldgroups(id, &[], access_token).await
+    }
+
+    /// Same as `get_item`, adding headers for this call only, merged like
+    /// `SearchConfig::with_headers`
+    pub async fn get_item_with_headers(
+        &self,
+        id: &str,
+        access_token: &str,
+        extra: &HeaderMap
+    ) -> Result<Item, EbayError> {
+        self.fetch_item(id, &[], access_token, Some(extra)).await
+    }
+
+    /// Fetch one listing with extra fieldgroups, see `get_item_with_fieldgroups`
+    pub async fn get_item_with_fieldgroups(
+        &self,
+        id: &str,
+        fieldgroups: &[ItemFieldGroup],
+        access_token: &str
+    ) -> Result<Item, EbayError> {
+        self.fetch_item(id, fieldgroups, access_token, None).await
+    }
+
+    async fn fetch_item(
+        &self,
+        id: &str,
+        fieldgroups: &[ItemFieldGroup],
+        access_token: &str,
+        extra: Option<&HeaderMap>
+    ) -> Result<Item, EbayError> {
+        let url = format!("{}/{}", self.endpoint(ITEM_PATH), id);
+        let headers = request_headers(access_token, extra);
+
+        let mut parameters: Vec<(&str, String)> = Vec::new();
+        if !fieldgroups.is_empty() {
+            let names: Vec<&str> = fieldgroups
+                .iter()
+                .map(|group| group.as_str())
+                .collect();
+            parameters.push(("fieldgroups", names.join(",")));
+        }
+
+        let body = self.get_item_body(&url, &headers, &parameters).await?;
+
+        serde_json::from_value(body).map_err(EbayError::Json)
+    }
+
+    // GET an item endpoint. If eBay reports the access token as expired and the client has a
+    // Token Manager, refresh the token and retry once, like a search does.
+    async fn get_item_body<P: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        parameters: &P
+    ) -> Result<Value, EbayError> {
+        match self.get(url, headers, parameters, 0).await {
+            Err(err) if err.is_expired_token() => {
+                let Some(access_token) = self.refreshed_token(None, headers).await else {
+                    return Err(err);
+                };
+
+                let mut headers = headers.clone();
+                headers.insert(header::AUTHORIZATION, bearer(&access_token?));
+                telemetry::record_retry("expired_token");
+
+                self.get(url, &headers, parameters, 1).await
+            }
+            result => result,
+        }
+    }
+
+    /// Fetch full details for a search result by following its `item_href`, the URL eBay
+    /// hands out for the listing, instead of rebuilding it from the item ID
+    pub async fn get_item_by_href(&self, href: &str, access_token: &str) -> Result<Item, EbayError> {
+        let headers = default_headers(access_token);
+
+        let body = self.get_item_body(href, &headers, &[] as &[(&str, &str)]).await?;
+
+        serde_json::from_value(body).map_err(EbayError::Json)
+    }
+
+    /// Fetch full details for a listing by its legacy item ID, see `get_item_by_legacy_id`
+    pub async fn get_item_by_legacy_id(
+        &self,
+        legacy_id: &str,
+        access_token: &str
+    ) -> Result<Item, EbayError> {
+        let url = format!("{}/get_item_by_legacy_id", self.endpoint(ITEM_PATH));
+        let headers = default_headers(access_token);
+        let parameters = [("legacy_item_id", legacy_id)];
+
+        let body = self.get_item_body(&url, &headers, &parameters).await?;
+
+        serde_json::from_value(body).map_err(EbayError::Json)
+    }
+
+    /// Fetch full details for many listings, see `get_items`
+    pub async fn get_items(&self, ids: &[String], access_token: &str) -> Result<Vec<Item>, EbayError> {
+        self.fetch_items(ids, access_token, None).await
+    }
+
+    /// Same as `get_items`, adding headers to every batch of this call only, merged like
+    /// `SearchConfig::with_headers`
+    pub async fn get_items_with_headers(
+        &self,
+        ids: &[String],
+        access_token: &str,
+        extra: &HeaderMap
+    ) -> Result<Vec<Item>, EbayError> {
+        self.fetch_items(ids, access_token, Some(extra)).await
+    }
+
+    async fn fetch_items(
+        &self,
+        ids: &[String],
+        access_token: &str,
+        extra: Option<&HeaderMap>
+    ) -> Result<Vec<Item>, EbayError> {
+        let url = format!("{}/", self.endpoint(ITEM_PATH));
+        let headers = request_headers(access_token, extra);
+
+        // Batches run concurrently, bounded by the client's request limiter
+        let batches = ids.chunks(MAX_ITEM_IDS_PER_REQUEST).map(|batch| {
+            let parameters = [("item_ids", batch.join(","))];
+            let url = &url;
+            let headers = &headers;
+
+            async move {
+                let body = self.get_item_body(url, headers, &parameters).await?;
+                serde_json::from_value::<ItemsResponse>(body).map_err(EbayError::Json)
+            }
+        });
+
+        let responses = try_join_all(batches).await?;
+
+        Ok(responses.into_iter().flat_map(|response| response.items).collect())
+    }
+
+    /// Fetch every variation of a listing by its item group ID, see `get_items_by_item_group`
+    pub async fn get_items_by_item_group(
+        &self,
+        item_group_id: &str,
+        access_token: &str
+    ) -> Result<Vec<Item>, EbayError> {
+        let url = format!("{}/get_items_by_item_group", self.endpoint(ITEM_PATH));
+        let headers = default_headers(access_token);
+        let parameters = [("item_group_id", item_group_id)];
+
+        let body = self.get_item_body(&url, &headers, &parameters).await?;
+
+        serde_json
+            ::from_value::<ItemsResponse>(body)
+            .map(|response| response.items)
+            .map_err(EbayError::Json)
+    }
+
+    /// Full details for every item in the search response, see `enrich`
+    pub async fn enrich(&self, response: &SearchResponse) -> Result<Vec<Item>, EbayError> {
+        let ids: Vec<String> = response.item_summaries
+            .iter()
+            .map(|item| item.item_id.clone())
+            .collect();
+        let access_token = self.access_token().await?;
+
+        self.get_items(&ids, &access_token).await
+    }
+
+    /// Fetch a mix of item IDs, legacy item IDs and item group IDs, see `get_items_and_groups`
+    pub async fn get_items_and_groups(
+        &self,
+        ids: &[ItemLookup],
+        access_token: &str
+    ) -> Result<Vec<Item>, EbayError> {
+        let mut item_ids = Vec::new();
+        let mut legacy_ids = Vec::new();
+        let mut group_ids = Vec::new();
+
+        for id in ids {
+            let (kind, id) = match id {
+                ItemLookup::Item(id) => (&mut item_ids, id),
+                ItemLookup::Legacy(id) => (&mut legacy_ids, id),
+                ItemLookup::Group(id) => (&mut group_ids, id),
+            };
+            if !id.trim().is_empty() {
+                kind.push(id.trim().to_string());
+            }
+        }
+
+        let legacy = legacy_ids
+            .iter()
+            .map(|legacy_id| self.get_item_by_legacy_id(legacy_id, access_token));
+        let groups = group_ids
+            .iter()
+            .map(|group_id| self.get_items_by_item_group(group_id, access_token));
+
+        let (mut items, legacy, groups) = try_join3(
+            self.get_items(&item_ids, access_token),
+            try_join_all(legacy),
+            try_join_all(groups)
+        ).await?;
+
+        items.extend(legacy);
+        items.extend(groups.into_iter().flatten());
+
+        Ok(items)
+    }
+}
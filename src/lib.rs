@@ -0,0 +1,29 @@
+//! # ebay-api-test
+//!
+//! Client for eBay's Browse API: keyword and filtered searches, pagination, item lookups
+//! and OAuth token handling. The `ebay-api-test` binary is a small command line front end
+//! to the `ebay_api` module; see the `examples` directory for the library used directly.
+
+#[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+compile_error!("enable a TLS backend with either the `rustls-tls` or the `native-tls` feature");
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod client;
+pub mod context;
+pub mod ebay_api;
+pub mod error;
+pub mod fieldgroups;
+pub mod filter;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod ingest;
+pub mod interceptor;
+pub mod item;
+pub mod marketplace;
+pub mod models;
+pub mod query;
+pub mod stream;
+pub mod telemetry;
+pub mod token;
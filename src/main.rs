@@ -1,14 +1,23 @@
-#[allow(unused)]
-use crate::ebay_api::ebay_api::SearchConfig;
-use toml;
+use crate::cli::Cli;
+use clap::Parser;
+use ebay_api_test::client::{ EbayClient, RetryConfig };
+use ebay_api_test::ebay_api::SearchConfig;
+use ebay_api_test::error::EbayError;
+use ebay_api_test::filter::SearchFilter;
+use ebay_api_test::models::SearchResponse;
 use serde_derive::Deserialize;
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
 
-pub mod ebay_api;
+mod cli;
 
 // Structure to hold important secret information
 #[derive(Debug, Deserialize)]
 struct ApiKeys {
     api_keys: ApiKeysInner,
+    #[serde(default)]
+    http: HttpSettings,
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,14 +25,100 @@ struct ApiKeysInner {
     ebay: String,
 }
 
+// Optional [http] section tuning how requests are sent
+#[derive(Debug, Default, Deserialize)]
+struct HttpSettings {
+    // Give up on a request after this many seconds
+    timeout_secs: Option<u64>,
+    // Send every request through this proxy, e.g. http://proxy.internal:3128
+    proxy: Option<String>,
+    // Retry a rate limited, failed or timed out search this many times
+    max_retries: Option<u32>,
+}
+
+impl HttpSettings {
+    fn validate(&self) -> Result<(), String> {
+        if self.timeout_secs == Some(0) {
+            return Err(String::from("http.timeout_secs must be greater than 0"));
+        }
+
+        if let Some(proxy) = &self.proxy {
+            reqwest::Proxy::all(proxy).map_err(|e| format!("http.proxy '{}' is invalid: {}", proxy, e))?;
+        }
+
+        Ok(())
+    }
+
+    // Client sending requests with these settings
+    fn client(&self) -> Result<EbayClient, Box<dyn std::error::Error>> {
+        let mut builder = reqwest::Client::builder().gzip(true).brotli(true);
+
+        if let Some(timeout_secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout_secs));
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        let retry = RetryConfig {
+            max_consecutive_errors: self.max_retries.unwrap_or(0) + 1,
+            ..RetryConfig::default()
+        };
+
+        Ok(EbayClient::with_http_client(builder.build()?).with_retry(retry))
+    }
+}
+
 // Read the config file to retrieve secret information
 fn read_config() -> Result<ApiKeys, Box<dyn std::error::Error>> {
     let config_str = std::fs::read_to_string("config.toml")?;
-    Ok(toml::from_str(&config_str)?)
+    let config: ApiKeys = toml::from_str(&config_str)?;
+    config.http.validate()?;
+    Ok(config)
+}
+
+// Run the request, retrying transient and connection errors as often as the client's
+// retry settings allow
+async fn retrying<T, F, Fut>(client: &EbayClient, mut send: F) -> Result<T, EbayError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, EbayError>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        match send().await {
+            Err(err) if
+                attempt < client.retry.max_consecutive_errors &&
+                (err.is_transient() || err.is_connection_error())
+            => {
+                let delay = client.retry.delay(attempt, err.retry_after());
+                tracing::warn!(error = %err, delay_ms = delay.as_millis() as u64, "retrying request");
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+// Run the search with retries
+async fn search(client: &EbayClient, config: SearchConfig) -> Result<SearchResponse, EbayError> {
+    retrying(client, || client.search(config.clone())).await
 }
 
-#[allow(unused)]
-fn main() {
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    // Diagnostics go to stderr so stdout carries nothing but the results
+    let log_level = if cli.quiet { tracing::Level::ERROR } else { tracing::Level::WARN };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(log_level)
+        .init();
+
     // Read API Key from Config File
     let api_keys = match read_config() {
         Ok(keys) => keys,
@@ -33,17 +128,76 @@ fn main() {
         }
     };
 
+    let client = match api_keys.http.client() {
+        Ok(client) => client.environment(cli.environment.environment()),
+        Err(e) => {
+            eprintln!("Error setting up the HTTP client: {}", e);
+            return;
+        }
+    };
+
     // Define request parameters
     // query: what we are searching for
-    let query: serde_json::Value = serde_json::Value::String(String::from("laptop"));
+    let query: serde_json::Value = serde_json::Value::String(cli.query);
+
+    // only the total is needed, which count reads from a single result
+    if cli.count_only {
+        let access_token = &api_keys.api_keys.ebay;
+        let count = retrying(&client, || client.count(query.clone(), SearchFilter::new(), access_token)).await;
+
+        match count {
+            Ok(total) => println!("{}", total),
+            Err(error) => {
+                eprintln!("Problem with the request: {}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
     // config: stuff we need to request - access token, headers, parameters, etc
-    let config = SearchConfig::new(query, api_keys.api_keys.ebay);
+    let config = SearchConfig::new(query, api_keys.api_keys.ebay)
+        .environment(cli.environment.environment());
 
     // post the query and print the results to the terminal
-    let result = ebay_api::ebay_api::post_query(config);
-    let outcome = match result {
-        Ok(file) => file,
-        Err(error) => panic!("Problem with the request: {:?}", error),
+    let mut response = match search(&client, config).await {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Problem with the request: {}", error);
+            std::process::exit(1);
+        }
     };
+
+    if let Some(sort) = cli.sort_client {
+        response.sort_items(sort.client_sort());
+    }
+
+    let json = if cli.compact {
+        serde_json::to_string(&response).expect("failed to serialize json")
+    } else {
+        serde_json::to_string_pretty(&response).expect("failed to pretty json")
+    };
+
+    // write the results to the output file if one was given, stdout otherwise, with nothing
+    // but the JSON on stdout so it can be piped
+    match &cli.output {
+        Some(path) => {
+            if let Err(e) = write_output(path, &json) {
+                eprintln!("Error writing results to {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        None => println!("{}", json),
+    }
+}
+
+// Write the results to a file, creating any missing parent directories
+fn write_output(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    std::fs::write(path, contents)
 }
@@ -0,0 +1,153 @@
+//! # Marketplace Module
+//!
+//! The `marketplace` module provides `Marketplace`, the eBay sites a search can run against.
+//! Each marketplace knows the ID sent in the `X-EBAY-C-MARKETPLACE-ID` header and the
+//! defaults that follow from it: the currency listings are priced in, the language eBay
+//! serves them in and the country its buyers are usually in.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use ebay_api_test::ebay_api::SearchConfig;
+//! use ebay_api_test::marketplace::Marketplace;
+//!
+//! let marketplace: Marketplace = "ebay_de".parse()?;
+//! assert_eq!(marketplace.currency(), "EUR");
+//!
+//! let config = SearchConfig::builder()
+//!     .query("laptop")
+//!     .marketplace(marketplace)
+//!     .build()?;
+//! # Ok::<(), ebay_api_test::error::EbayError>(())
+//! ```
+
+use std::fmt;
+
+use crate::error::EbayError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// An eBay site, identified on the wire by its marketplace ID
+pub enum Marketplace {
+    #[default]
+    /// `EBAY_US`
+    UnitedStates,
+    /// `EBAY_MOTORS`, a separate marketplace sharing the US currency, language and buyers
+    Motors,
+    /// `EBAY_GB`
+    UnitedKingdom,
+    /// `EBAY_AU`
+    Australia,
+    /// `EBAY_CA`
+    Canada,
+    /// `EBAY_DE`
+    Germany,
+    /// `EBAY_AT`
+    Austria,
+    /// `EBAY_CH`
+    Switzerland,
+    /// `EBAY_FR`
+    France,
+    /// `EBAY_IT`
+    Italy,
+    /// `EBAY_ES`
+    Spain,
+}
+
+impl Marketplace {
+    /// Every marketplace this crate knows
+    pub const ALL: [Marketplace; 11] = [
+        Marketplace::UnitedStates,
+        Marketplace::Motors,
+        Marketplace::UnitedKingdom,
+        Marketplace::Australia,
+        Marketplace::Canada,
+        Marketplace::Germany,
+        Marketplace::Austria,
+        Marketplace::Switzerland,
+        Marketplace::France,
+        Marketplace::Italy,
+        Marketplace::Spain,
+    ];
+
+    /// Marketplace ID sent in the `X-EBAY-C-MARKETPLACE-ID` header, e.g. `EBAY_GB`
+    pub fn id(&self) -> &'static str {
+        match self {
+            Marketplace::UnitedStates => "EBAY_US",
+            Marketplace::Motors => "EBAY_MOTORS",
+            Marketplace::UnitedKingdom => "EBAY_GB",
+            Marketplace::Australia => "EBAY_AU",
+            Marketplace::Canada => "EBAY_CA",
+            Marketplace::Germany => "EBAY_DE",
+            Marketplace::Austria => "EBAY_AT",
+            Marketplace::Switzerland => "EBAY_CH",
+            Marketplace::France => "EBAY_FR",
+            Marketplace::Italy => "EBAY_IT",
+            Marketplace::Spain => "EBAY_ES",
+        }
+    }
+
+    /// ISO 4217 code of the currency listings are priced in
+    pub fn currency(&self) -> &'static str {
+        match self {
+            Marketplace::UnitedStates | Marketplace::Motors => "USD",
+            Marketplace::UnitedKingdom => "GBP",
+            Marketplace::Australia => "AUD",
+            Marketplace::Canada => "CAD",
+            Marketplace::Switzerland => "CHF",
+            Marketplace::Germany | Marketplace::Austria | Marketplace::France |
+            Marketplace::Italy | Marketplace::Spain => "EUR",
+        }
+    }
+
+    /// Language eBay serves listings in, sent as `Accept-Language` unless overridden
+    pub fn locale(&self) -> &'static str {
+        match self {
+            Marketplace::UnitedStates | Marketplace::Motors => "en-US",
+            Marketplace::UnitedKingdom => "en-GB",
+            Marketplace::Australia => "en-AU",
+            Marketplace::Canada => "en-CA",
+            Marketplace::Germany => "de-DE",
+            Marketplace::Austria => "de-AT",
+            Marketplace::Switzerland => "de-CH",
+            Marketplace::France => "fr-FR",
+            Marketplace::Italy => "it-IT",
+            Marketplace::Spain => "es-ES",
+        }
+    }
+
+    /// Two letter code of the country the marketplace's buyers are usually in
+    pub fn country(&self) -> &'static str {
+        match self {
+            Marketplace::UnitedStates | Marketplace::Motors => "US",
+            Marketplace::UnitedKingdom => "GB",
+            Marketplace::Australia => "AU",
+            Marketplace::Canada => "CA",
+            Marketplace::Germany => "DE",
+            Marketplace::Austria => "AT",
+            Marketplace::Switzerland => "CH",
+            Marketplace::France => "FR",
+            Marketplace::Italy => "IT",
+            Marketplace::Spain => "ES",
+        }
+    }
+}
+
+impl fmt::Display for Marketplace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+impl std::str::FromStr for Marketplace {
+    type Err = EbayError;
+
+    /// Parse a marketplace ID such as `EBAY_GB`, ignoring case and surrounding whitespace
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let id = value.trim().to_ascii_uppercase();
+
+        Marketplace::ALL
+            .into_iter()
+            .find(|marketplace| marketplace.id() == id)
+            .ok_or_else(|| EbayError::Config(format!("unknown marketplace '{}'", value.trim())))
+    }
+}
@@ -0,0 +1,894 @@
+//! # Models Module
+//!
+//! The `models` module holds the typed structures eBay's Browse API responses are
+//! deserialized into. Field names follow the Rust convention and are mapped from eBay's
+//! camelCase JSON; anything eBay leaves out of a response defaults to `None` or empty.
+//!
+//! Counts, offsets and totals are `u64`, so large result counts never overflow or lose
+//! precision. Money stays a decimal string, exactly as eBay sends it, rather than an `f64`;
+//! helpers that need a number (`title_price_pairs`, `ClientSort::Price`) parse it on demand.
+
+use std::fmt;
+use std::time::Duration;
+use serde::Deserialize as _;
+use serde_derive::{ Deserialize, Serialize };
+use serde_json::Value;
+
+use crate::ebay_api::SearchConfig;
+use crate::filter::{ aspect_entry, BuyingOption, Condition };
+
+#[cfg(feature = "chrono")]
+/// Point in time eBay reports, parsed from its ISO 8601 form
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+#[cfg(not(feature = "chrono"))]
+/// Point in time eBay reports, as the ISO 8601 string it was sent as.
+/// Enable the `chrono` feature to get `chrono::DateTime<Utc>` instead.
+pub type Timestamp = String;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Amount of money as eBay reports it, a decimal string plus an ISO 4217 currency code
+pub struct Price {
+    pub value: String,
+    pub currency: String,
+}
+
+impl Price {
+    /// The value as a number, None when it doesn't parse
+    pub fn amount(&self) -> Option<f64> {
+        self.value.trim().parse().ok()
+    }
+}
+
+impl fmt::Display for Price {
+    /// Formats the price the way buyers on the currency's marketplaces read it, e.g.
+    /// `$149.99`, `£149.99` or `149,99 €`. Other currencies, and values that aren't a plain
+    /// decimal, are written as `<value> <currency>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.value.trim();
+        let currency = self.currency.trim();
+
+        if self.amount().is_none() {
+            return write!(f, "{} {}", value, currency);
+        }
+
+        match currency {
+            "USD" => write!(f, "${}", value),
+            "GBP" => write!(f, "£{}", value),
+            "AUD" => write!(f, "AU${}", value),
+            "CAD" => write!(f, "C${}", value),
+            "CHF" => write!(f, "CHF {}", value),
+            "EUR" => write!(f, "{} €", value.replace('.', ",")),
+            _ => write!(f, "{} {}", value, currency),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+/// Condition of a listing, from the `condition` label ("New", "Used", ...) and the
+/// numeric `conditionId` eBay sends side by side
+pub struct ItemCondition {
+    #[serde(rename = "condition")]
+    pub label: Option<String>,
+    #[serde(rename = "conditionId")]
+    pub id: Option<String>,
+}
+
+impl ItemCondition {
+    /// Typed condition, `Condition::Other` for IDs this crate doesn't know,
+    /// None when eBay sent no ID
+    pub fn kind(&self) -> Option<Condition> {
+        self.id.as_deref().map(Condition::from_id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Listing picture. eBay only sometimes reports its dimensions.
+pub struct Image {
+    pub image_url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Full details of a single listing, as returned by the item endpoints
+pub struct Item {
+    pub item_id: String,
+    #[serde(default)]
+    pub title: String,
+    pub price: Option<Price>,
+    #[serde(flatten)]
+    pub condition: ItemCondition,
+    pub item_web_url: Option<String>,
+    pub image: Option<Image>,
+    #[serde(default)]
+    pub additional_images: Vec<Image>,
+    #[serde(default)]
+    pub buying_options: Vec<BuyingOption>,
+    /// Catalog data for the product being sold, only sent for the `PRODUCT` fieldgroup
+    pub product: Option<Product>,
+    /// Stock eBay expects to be available, per way of delivering the item
+    #[serde(default)]
+    pub estimated_availabilities: Vec<EstimatedAvailability>,
+    /// Full description written by the seller, as HTML. Only the item endpoints send it,
+    /// never the search.
+    pub description: Option<String>,
+    /// Plain text summary of the description, when the seller wrote one
+    pub short_description: Option<String>,
+}
+
+impl Item {
+    #[cfg(feature = "html")]
+    /// The description with its HTML stripped, see `html::strip_html`
+    pub fn description_text(&self) -> Option<String> {
+        self.description.as_deref().map(crate::html::strip_html)
+    }
+
+    /// True when eBay reports every estimate as out of stock. An item without estimates
+    /// isn't counted as out of stock.
+    pub fn is_out_of_stock(&self) -> bool {
+        !self.estimated_availabilities.is_empty() &&
+            self.estimated_availabilities
+                .iter()
+                .all(|availability| {
+                    availability.estimated_availability_status == Some(AvailabilityStatus::OutOfStock)
+                })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// How much of a listing eBay expects to be available for some ways of delivering it
+pub struct EstimatedAvailability {
+    pub estimated_availability_status: Option<AvailabilityStatus>,
+    pub estimated_available_quantity: Option<u64>,
+    pub estimated_sold_quantity: Option<u64>,
+    /// Ways of delivery the estimate is for, e.g. `SHIP_TO_HOME`
+    #[serde(default)]
+    pub delivery_options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// Stock level eBay estimates for a listing
+pub enum AvailabilityStatus {
+    InStock,
+    LimitedStock,
+    OutOfStock,
+    /// A value this crate doesn't know yet
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Catalog product a listing is for, from eBay's product catalog rather than the seller
+pub struct Product {
+    pub title: Option<String>,
+    pub brand: Option<String>,
+    pub description: Option<String>,
+    /// Manufacturer part numbers
+    #[serde(default)]
+    pub mpns: Vec<String>,
+    /// Global trade item numbers, e.g. UPCs or EANs
+    #[serde(default)]
+    pub gtins: Vec<String>,
+    #[serde(default)]
+    pub aspect_groups: Vec<AspectGroup>,
+    pub image: Option<Image>,
+    #[serde(default)]
+    pub additional_images: Vec<Image>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Named group of catalog aspects, e.g. `Key Features`
+pub struct AspectGroup {
+    pub localized_group_name: Option<String>,
+    #[serde(default)]
+    pub aspects: Vec<Aspect>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// One aspect of a product and its values, e.g. `Storage Capacity: 256 GB`
+pub struct Aspect {
+    pub localized_name: String,
+    #[serde(default)]
+    pub localized_values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Shipping cost and delivery estimate for one way of shipping a listing.
+/// eBay estimates these for the buyer location given by the delivery filters.
+pub struct ShippingOption {
+    pub shipping_cost: Option<Price>,
+    pub shipping_cost_type: Option<String>,
+    pub min_estimated_delivery_date: Option<String>,
+    pub max_estimated_delivery_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// One listing in a page of search results
+pub struct ItemSummary {
+    pub item_id: String,
+    #[serde(default)]
+    pub title: String,
+    /// None when eBay hides the price, see `price_display_condition`
+    pub price: Option<Price>,
+    /// When the price is shown to buyers; eBay leaves `price` out for listings whose
+    /// price is only shown once the item is in the cart
+    pub price_display_condition: Option<PriceDisplayCondition>,
+    /// Discount shown next to the price, None when the listing isn't discounted
+    pub marketing_price: Option<MarketingPrice>,
+    #[serde(flatten)]
+    pub condition: ItemCondition,
+    /// Link to the listing in a browser
+    pub item_web_url: Option<String>,
+    /// API link to the listing's full details, see `EbayClient::get_item_by_href`
+    pub item_href: Option<String>,
+    #[serde(default)]
+    pub shipping_options: Vec<ShippingOption>,
+    pub image: Option<Image>,
+    #[serde(default)]
+    pub thumbnail_images: Vec<Image>,
+    /// Whether the listing is an auction, fixed price, accepts offers, ...
+    #[serde(default)]
+    pub buying_options: Vec<BuyingOption>,
+    /// When the listing was created
+    pub item_creation_date: Option<Timestamp>,
+    /// When the listing ends, mostly set for auctions
+    pub item_end_date: Option<Timestamp>,
+    /// Listing is restricted to adults
+    #[serde(default)]
+    pub adult_only: bool,
+    /// Listing qualifies for eBay's Top Rated buying experience
+    #[serde(default)]
+    pub top_rated_buying_experience: bool,
+    /// Price per unit for goods sold by quantity, e.g. per 100g, in `unit_pricing_measure`
+    pub unit_price: Option<Price>,
+    /// Unit `unit_price` is for, e.g. `100g` or `Kilogram`
+    pub unit_pricing_measure: Option<String>,
+    /// How well a part fits the vehicle in the search's `compatibility_filter`
+    pub compatibility_match: Option<CompatibilityMatch>,
+}
+
+impl fmt::Display for ItemSummary {
+    /// Title and price, e.g. `ThinkPad X1 Carbon: £149.99`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.price {
+            Some(price) => write!(f, "{}: {}", self.title, price),
+            None => write!(f, "{}: price shown in cart", self.title),
+        }
+    }
+}
+
+impl ItemSummary {
+    /// Amount saved on a discounted listing, see `MarketingPrice::savings`. None when the
+    /// listing isn't discounted.
+    pub fn savings(&self) -> Option<Price> {
+        self.marketing_price.as_ref()?.savings(self.price.as_ref())
+    }
+
+    /// Legacy item ID encoded in the RESTful item ID, e.g. `110551991234` for
+    /// `v1|110551991234|0`, as used by the Finding and Trading APIs. None when the item ID
+    /// doesn't have that shape.
+    pub fn legacy_item_id(&self) -> Option<String> {
+        let mut parts = self.item_id.split('|');
+
+        let version = parts.next()?;
+        let legacy_id = parts.next()?;
+        let _variation_id = parts.next()?;
+
+        if parts.next().is_some() || !version.starts_with('v') {
+            return None;
+        }
+        if legacy_id.is_empty() || !legacy_id.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        Some(legacy_id.to_string())
+    }
+
+    /// Cheapest shipping cost among the shipping options, None when no option has a
+    /// cost that parses as a number
+    pub fn cheapest_shipping(&self) -> Option<f64> {
+        self.shipping_options
+            .iter()
+            .filter_map(|option| option.shipping_cost.as_ref()?.amount())
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Price plus the cheapest shipping option, what the buyer actually pays. An item
+    /// without a known shipping cost counts as shipping for free with `UnknownShipping::Free`
+    /// and has no total with `UnknownShipping::Last`. None when the price is hidden or doesn't parse.
+    pub fn total_cost(&self, unknown_shipping: UnknownShipping) -> Option<f64> {
+        let price = self.price.as_ref()?.amount()?;
+
+        match (self.cheapest_shipping(), unknown_shipping) {
+            (Some(shipping), _) => Some(price + shipping),
+            (None, UnknownShipping::Free) => Some(price),
+            (None, UnknownShipping::Last) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// When eBay shows a listing's price to buyers
+pub enum PriceDisplayCondition {
+    AlwaysShow,
+    /// The seller only shows the price once the item is in the cart
+    OnlyShowWhenAddedInCart,
+    /// A value this crate doesn't know yet
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A discounted listing's price before the discount, shown struck through next to `price`
+pub struct MarketingPrice {
+    pub original_price: Option<Price>,
+    /// Percentage off the original price, e.g. `"20"`
+    pub discount_percentage: Option<String>,
+    pub discount_amount: Option<Price>,
+    /// How the discount is presented to buyers
+    pub price_treatment: Option<PriceTreatment>,
+}
+
+impl MarketingPrice {
+    /// Amount saved off the original price: `discount_amount` when eBay sent it, otherwise
+    /// the difference between `original_price` and the given current price when both are
+    /// in the same currency. None when neither is known.
+    pub fn savings(&self, price: Option<&Price>) -> Option<Price> {
+        if let Some(amount) = &self.discount_amount {
+            return Some(amount.clone());
+        }
+
+        let original = self.original_price.as_ref()?;
+        let price = price.filter(|price| price.currency == original.currency)?;
+        let saved = original.amount()? - price.amount()?;
+
+        Some(Price { value: format!("{:.2}", saved), currency: original.currency.clone() })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+/// How eBay presents a discount next to the price
+pub enum PriceTreatment {
+    /// The original price is a list price, such as the manufacturer's suggested price
+    ListPrice,
+    /// The seller marked the item down from its own earlier price
+    Markdown,
+    /// The manufacturer's minimum advertised price applies; eBay only shows the final
+    /// price once the item is in the cart
+    MinimumAdvertisedPrice,
+    /// A value this crate doesn't know yet
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How items without a known shipping cost are treated when sorting by total cost
+pub enum UnknownShipping {
+    /// Sort them after every item with a known total
+    #[default]
+    Last,
+    /// Count their shipping as free
+    Free,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+/// Whether a part fits the vehicle given in a `compatibility_filter`
+pub enum CompatibilityMatch {
+    /// The part fits the vehicle
+    Exact,
+    /// The part fits some variants of the vehicle, more details are needed to be sure
+    Possible,
+    /// A value this crate doesn't know, kept as eBay sent it
+    Other(String),
+}
+
+impl CompatibilityMatch {
+    /// Value eBay uses for this match
+    pub fn as_str(&self) -> &str {
+        match self {
+            CompatibilityMatch::Exact => "EXACT",
+            CompatibilityMatch::Possible => "POSSIBLE",
+            CompatibilityMatch::Other(value) => value,
+        }
+    }
+}
+
+impl From<String> for CompatibilityMatch {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "EXACT" => CompatibilityMatch::Exact,
+            "POSSIBLE" => CompatibilityMatch::Possible,
+            _ => CompatibilityMatch::Other(value),
+        }
+    }
+}
+
+impl From<CompatibilityMatch> for String {
+    fn from(value: CompatibilityMatch) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Warning eBay attaches to an otherwise successful response, e.g. for an ignored filter
+pub struct ApiWarning {
+    pub error_id: u32,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// How many results carry one value of an aspect
+pub struct AspectValueDistribution {
+    pub localized_aspect_value: String,
+    #[serde(default)]
+    pub match_count: u64,
+    pub refinement_href: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Values of one aspect (e.g. `Brand`) found across the results
+pub struct AspectDistribution {
+    pub localized_aspect_name: String,
+    #[serde(default)]
+    pub aspect_value_distributions: Vec<AspectValueDistribution>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// How many results are listed in one category
+pub struct CategoryDistribution {
+    pub category_id: String,
+    pub category_name: Option<String>,
+    #[serde(default)]
+    pub match_count: u64,
+    pub refinement_href: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// How many results offer one way to buy
+pub struct BuyingOptionDistribution {
+    pub buying_option: BuyingOption,
+    #[serde(default)]
+    pub match_count: u64,
+    pub refinement_href: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// How many results are in one condition
+pub struct ConditionDistribution {
+    pub condition: Option<String>,
+    pub condition_id: Option<String>,
+    #[serde(default)]
+    pub match_count: u64,
+    pub refinement_href: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Refinements eBay returns when the search asks for a refinement fieldgroup, such as
+/// `ASPECT_REFINEMENTS` or `CATEGORY_REFINEMENTS`. Add `MATCHING_ITEMS` to get the item
+/// summaries in the same response.
+///
+/// Each distribution's `match_count` is the number of matching listings with that value,
+/// counted before any refinement is applied. It differs from the response's `total`: a
+/// listing can appear under several values (say, several buying options) or under none,
+/// so the counts don't have to add up to `total`.
+pub struct Refinement {
+    pub dominant_category_id: Option<String>,
+    #[serde(default)]
+    pub aspect_distributions: Vec<AspectDistribution>,
+    #[serde(default)]
+    pub category_distributions: Vec<CategoryDistribution>,
+    #[serde(default)]
+    pub buying_option_distributions: Vec<BuyingOptionDistribution>,
+    #[serde(default)]
+    pub condition_distributions: Vec<ConditionDistribution>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+/// Keywords eBay searched for instead of the ones sent, see `SearchConfigBuilder::auto_correct`
+pub struct AutoCorrections {
+    /// The corrected `q`
+    pub q: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Order `SearchResponse::sort_items` puts a page in after it was fetched. Unlike eBay's
+/// `sort` parameter it is stable, so the same results always come out in the same order.
+pub enum ClientSort {
+    /// Lowest price first, ties broken by item ID. Missing or unparseable prices go last.
+    Price,
+    /// By item ID
+    ItemId,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A page of results from the item summary search endpoint
+pub struct SearchResponse {
+    pub href: Option<String>,
+    #[serde(default)]
+    pub total: u64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+    #[serde(default)]
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+    #[serde(default)]
+    pub item_summaries: Vec<ItemSummary>,
+    #[serde(default)]
+    pub warnings: Vec<ApiWarning>,
+    pub refinement: Option<Refinement>,
+    /// Corrections eBay made to the search, only sent when `auto_correct` is enabled
+    pub auto_corrections: Option<AutoCorrections>,
+    /// Config that produced this response, set by `EbayClient::search`
+    #[serde(skip)]
+    pub config: Option<SearchConfig>,
+    /// How long the request and reading the body took, measured by the client.
+    /// A response served from the cache keeps the time of the original request.
+    #[serde(skip)]
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A page of search results with each item summary parsed into `T`, a projection holding
+/// only the fields the caller needs. Fields `T` doesn't declare are skipped by the parser
+/// without being built: `ItemIdPrice` parses a 200 item page in under half the time of a
+/// full `SearchResponse`, see `benches/projection.rs`.
+pub struct SearchPage<T> {
+    #[serde(default)]
+    pub total: u64,
+    pub next: Option<String>,
+    #[serde(default)]
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+    #[serde(default = "Vec::new")]
+    pub item_summaries: Vec<T>,
+    #[serde(default)]
+    pub warnings: Vec<ApiWarning>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A page of results together with its aspect facets, everything a faceted search front
+/// end needs from one call, see `EbayClient::search_with_facets`
+pub struct SearchResult {
+    pub items: Vec<ItemSummary>,
+    /// Aspects of the matching items with the values found and how many items have each
+    pub facets: Vec<AspectDistribution>,
+    /// Number of matches across every page
+    pub total: u64,
+    /// Offset of the following page, None on the last page
+    pub next_offset: Option<u64>,
+}
+
+impl From<SearchResponse> for SearchResult {
+    fn from(response: SearchResponse) -> Self {
+        let next_offset = response.next
+            .as_ref()
+            .map(|_| response.offset + (response.item_summaries.len() as u64));
+
+        SearchResult {
+            items: response.item_summaries,
+            facets: response.refinement
+                .map(|refinement| refinement.aspect_distributions)
+                .unwrap_or_default(),
+            total: response.total,
+            next_offset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Projection of an item summary down to its ID and price, for use with `SearchPage`
+pub struct ItemIdPrice {
+    pub item_id: String,
+    pub price: Option<Price>,
+}
+
+#[derive(Debug)]
+/// An item summary that failed to parse in a lenient parse, see `SearchResponse::from_slice_lenient`
+pub struct ItemParseError {
+    /// Position of the item in the page's `itemSummaries`
+    pub index: usize,
+    pub error: serde_json::Error,
+    /// The item as eBay sent it
+    pub raw: Value,
+}
+
+impl SearchResponse {
+    /// Parse a search response body, skipping item summaries that don't parse instead of
+    /// failing the whole page. The skipped items come back with their index and error.
+    /// Fails only when the body isn't JSON or the page itself doesn't parse.
+    pub fn from_slice_lenient(body: &[u8]) -> Result<(SearchResponse, Vec<ItemParseError>), serde_json::Error> {
+        let mut page: Value = serde_json::from_slice(body)?;
+
+        let raw_items = match page.get_mut("itemSummaries").map(Value::take) {
+            Some(Value::Array(items)) => items,
+            _ => Vec::new(),
+        };
+
+        let mut response: SearchResponse = serde_json::from_value(page)?;
+        let mut failures = Vec::new();
+
+        for (index, raw) in raw_items.into_iter().enumerate() {
+            match ItemSummary::deserialize(&raw) {
+                Ok(item) => response.item_summaries.push(item),
+                Err(error) => failures.push(ItemParseError { index, error, raw }),
+            }
+        }
+
+        Ok((response, failures))
+    }
+
+    /// Reorder the item summaries on this page, e.g. to get reproducible output from a
+    /// best match search, whose order can change from one run to the next
+    pub fn sort_items(&mut self, by: ClientSort) {
+        match by {
+            ClientSort::Price => self.item_summaries.sort_by(|a, b| {
+                let a_price = a.price.as_ref().and_then(Price::amount).unwrap_or(f64::INFINITY);
+                let b_price = b.price.as_ref().and_then(Price::amount).unwrap_or(f64::INFINITY);
+
+                a_price.total_cmp(&b_price).then_with(|| a.item_id.cmp(&b.item_id))
+            }),
+            ClientSort::ItemId => self.item_summaries.sort_by(|a, b| a.item_id.cmp(&b.item_id)),
+        }
+    }
+
+    /// The items on this page ordered by total cost, price plus the cheapest shipping option,
+    /// lowest first, with ties broken by item ID. Items without a total go last.
+    pub fn sorted_by_total_cost(&self, unknown_shipping: UnknownShipping) -> Vec<ItemSummary> {
+        let mut items = self.item_summaries.clone();
+
+        items.sort_by(|a, b| {
+            let a_total = a.total_cost(unknown_shipping).unwrap_or(f64::INFINITY);
+            let b_total = b.total_cost(unknown_shipping).unwrap_or(f64::INFINITY);
+
+            a_total.total_cmp(&b_total).then_with(|| a.item_id.cmp(&b.item_id))
+        });
+
+        items
+    }
+
+    /// Title and numeric price of every item on the page, e.g. for a quick look at the
+    /// cheapest listings. Items without a price that parses as a number are left out.
+    pub fn title_price_pairs(&self) -> Vec<(String, f64)> {
+        self.item_summaries
+            .iter()
+            .filter_map(|item| {
+                let price = item.price.as_ref()?.amount()?;
+                Some((item.title.clone(), price))
+            })
+            .collect()
+    }
+
+    /// Query eBay searched for after correcting the keywords, e.g. `laptop` for `labtop`,
+    /// to show "Showing results for 'laptop' instead". None when nothing was corrected.
+    pub fn corrected_query(&self) -> Option<&str> {
+        self.auto_corrections
+            .as_ref()?
+            .q
+            .as_deref()
+            .filter(|q| !q.trim().is_empty())
+    }
+
+    /// Config for the same search narrowed to listings whose aspect has the given value,
+    /// e.g. `refine_with("Brand", "Apple")`. Aspects already filtered on are kept, and the
+    /// search restarts from the first page.
+    ///
+    /// eBay only accepts an aspect filter together with a category, so the search's
+    /// `category_ids` is used, falling back to the dominant category of this response,
+    /// which is then also set as `category_ids`. Returns None when the response did not
+    /// come from `EbayClient::search` or no category is known.
+    pub fn refine_with(&self, aspect_name: &str, value: &str) -> Option<SearchConfig> {
+        let mut config = self.config.clone()?;
+
+        let searched_category = config.search_parameters
+            .get("category_ids")
+            .and_then(|ids| ids.as_str())
+            .and_then(|ids| ids.split(',').next())
+            .filter(|id| !id.trim().is_empty())
+            .map(|id| id.trim().to_string());
+
+        let category_id = match searched_category {
+            Some(category_id) => category_id,
+            None => {
+                let dominant = self.refinement.as_ref()?.dominant_category_id.clone()?;
+                config.search_parameters.insert(String::from("category_ids"), Value::String(dominant.clone()));
+                dominant
+            }
+        };
+
+        let aspect = aspect_entry(aspect_name, value);
+        let aspect_filter = match config.search_parameters.get("aspect_filter") {
+            Some(Value::String(existing)) => format!("{},{}", existing, aspect),
+            _ => format!("categoryId:{},{}", category_id, aspect),
+        };
+
+        config.search_parameters.insert(String::from("aspect_filter"), Value::String(aspect_filter));
+        config.search_parameters.remove("offset");
+
+        Some(config)
+    }
+}
+
+impl IntoIterator for SearchResponse {
+    type Item = ItemSummary;
+    type IntoIter = std::vec::IntoIter<ItemSummary>;
+
+    /// Iterate over the item summaries on this page
+    fn into_iter(self) -> Self::IntoIter {
+        self.item_summaries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SearchResponse {
+    type Item = &'a ItemSummary;
+    type IntoIter = std::slice::Iter<'a, ItemSummary>;
+
+    /// Iterate over references to the item summaries on this page
+    fn into_iter(self) -> Self::IntoIter {
+        self.item_summaries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(config: SearchConfig, dominant_category_id: Option<&str>) -> SearchResponse {
+        SearchResponse {
+            config: Some(config),
+            refinement: Some(Refinement {
+                dominant_category_id: dominant_category_id.map(str::to_string),
+                ..Refinement::default()
+            }),
+            ..SearchResponse::default()
+        }
+    }
+
+    fn param<'a>(config: &'a SearchConfig, key: &str) -> Option<&'a str> {
+        config.search_parameters.get(key)?.as_str()
+    }
+
+    #[test]
+    fn refine_with_keeps_the_searched_category() {
+        let config = SearchConfig::builder().query("phone").category_ids(&["9355"]).build().unwrap();
+
+        let refined = response(config, Some("15032")).refine_with("Brand", "Apple").unwrap();
+
+        assert_eq!(param(&refined, "category_ids"), Some("9355"));
+        assert_eq!(param(&refined, "aspect_filter"), Some("categoryId:9355,Brand:{Apple}"));
+    }
+
+    #[test]
+    fn refine_with_falls_back_to_the_dominant_category() {
+        let config = SearchConfig::builder().query("phone").build().unwrap();
+
+        let refined = response(config, Some("9355")).refine_with("Brand", "Apple").unwrap();
+
+        assert_eq!(param(&refined, "category_ids"), Some("9355"));
+        assert_eq!(param(&refined, "aspect_filter"), Some("categoryId:9355,Brand:{Apple}"));
+        assert!(refined.validate().is_empty(), "{:?}", refined.validate());
+    }
+
+    #[test]
+    fn refine_with_needs_a_category() {
+        let config = SearchConfig::builder().query("phone").build().unwrap();
+
+        assert!(response(config, None).refine_with("Brand", "Apple").is_none());
+    }
+
+    #[test]
+    fn counts_past_u32_deserialize() {
+        let page = serde_json::json!({
+            "total": 5_000_000_000u64,
+            "limit": 200,
+            "offset": 4_294_967_296u64,
+            "itemSummaries": [],
+        });
+
+        let response: SearchResponse = serde_json::from_value(page.clone()).unwrap();
+        let projected: SearchPage<ItemIdPrice> = serde_json::from_value(page).unwrap();
+
+        assert_eq!(response.total, 5_000_000_000);
+        assert_eq!(response.offset, 4_294_967_296);
+        assert_eq!(projected.total, 5_000_000_000);
+        assert_eq!(projected.offset, 4_294_967_296);
+    }
+
+    #[test]
+    fn price_display_condition_present_and_absent() {
+        let page = serde_json::json!({
+            "total": 3,
+            "itemSummaries": [
+                {
+                    "itemId": "v1|1|0",
+                    "title": "Shown price",
+                    "price": { "value": "499.99", "currency": "USD" },
+                    "priceDisplayCondition": "ALWAYS_SHOW",
+                },
+                { "itemId": "v1|2|0", "title": "Price in cart", "priceDisplayCondition": "ONLY_SHOW_WHEN_ADDED_IN_CART" },
+                { "itemId": "v1|3|0", "title": "Older listing", "price": { "value": "20.00", "currency": "USD" } },
+            ],
+        });
+
+        let items = serde_json::from_value::<SearchResponse>(page).unwrap().item_summaries;
+
+        assert_eq!(items[0].price_display_condition, Some(PriceDisplayCondition::AlwaysShow));
+        assert_eq!(items[0].price.as_ref().and_then(Price::amount), Some(499.99));
+        assert_eq!(items[1].price_display_condition, Some(PriceDisplayCondition::OnlyShowWhenAddedInCart));
+        assert!(items[1].price.is_none());
+        assert_eq!(items[2].price_display_condition, None);
+        assert_eq!(items[2].price.as_ref().and_then(Price::amount), Some(20.0));
+    }
+
+    #[test]
+    fn unknown_price_display_condition_is_kept_as_unknown() {
+        let item: ItemSummary = serde_json::from_value(serde_json::json!({
+            "itemId": "v1|1|0",
+            "priceDisplayCondition": "SHOW_ON_TUESDAYS",
+        })).unwrap();
+
+        assert_eq!(item.price_display_condition, Some(PriceDisplayCondition::Unknown));
+    }
+
+    #[test]
+    fn missing_counts_default_to_zero() {
+        let response: SearchResponse = serde_json::from_str("{}").unwrap();
+
+        assert_eq!((response.total, response.limit, response.offset), (0, 0, 0));
+    }
+
+    fn price(value: &str, currency: &str) -> Price {
+        Price { value: value.to_string(), currency: currency.to_string() }
+    }
+
+    #[test]
+    fn price_displays_each_currency_its_own_way() {
+        assert_eq!(price("149.99", "USD").to_string(), "$149.99");
+        assert_eq!(price("149.99", "GBP").to_string(), "£149.99");
+        assert_eq!(price("149.99", "AUD").to_string(), "AU$149.99");
+        assert_eq!(price("149.99", "CAD").to_string(), "C$149.99");
+        assert_eq!(price("149.99", "CHF").to_string(), "CHF 149.99");
+        assert_eq!(price("149.99", "JPY").to_string(), "149.99 JPY");
+    }
+
+    #[test]
+    fn euro_price_uses_a_decimal_comma() {
+        assert_eq!(price("149.99", "EUR").to_string(), "149,99 €");
+        assert_eq!(price("149", "EUR").to_string(), "149 €");
+        assert_eq!(price(" 149.50 ", " EUR ").to_string(), "149,50 €");
+    }
+
+    #[test]
+    fn price_that_is_not_a_number_is_written_as_sent() {
+        assert_eq!(price("about 150", "EUR").to_string(), "about 150 EUR");
+        assert_eq!(price("", "USD").to_string(), " USD");
+    }
+}
@@ -0,0 +1,210 @@
+//! # Query Module
+//!
+//! The `query` module provides `Query`, a small builder for the `q` search parameter that
+//! renders eBay's keyword grammar so callers don't have to remember it.
+//!
+//! ## Grammar
+//!
+//! - Terms separated by spaces must all match: `black leather jacket`.
+//! - Terms in parentheses separated by commas match any of them: `(iphone,ipad)`.
+//! - A term prefixed with `-` excludes listings containing it: `-refurbished`.
+//! - Terms containing whitespace are quoted so they match as a phrase: `"new york"`.
+//!
+//! ## Example Usage
+//!
+//! ```rust
+//! use ebay_api_test::ebay_api::SearchConfig;
+//! use ebay_api_test::query::Query;
+//! # let access_token = String::from("Your-OAuth-Access-Token");
+//!
+//! // laptop (thinkpad,latitude) -refurbished
+//! let query = Query::new("laptop")
+//!     .and(Query::any_of(&["thinkpad", "latitude"]))
+//!     .and(Query::exclude("refurbished"));
+//!
+//! let config = SearchConfig::new(query.into(), access_token);
+//! ```
+//!
+//! Queries combine with a category restriction on the builder, e.g. laptops that aren't
+//! refurbished within category 177 (PC Laptops & Netbooks):
+//!
+//! ```rust
+//! # use ebay_api_test::ebay_api::SearchConfig;
+//! # use ebay_api_test::query::Query;
+//! # let access_token = "Your-OAuth-Access-Token";
+//! // q=laptop -refurbished&category_ids=177
+//! let config = SearchConfig::builder()
+//!     .query(Query::new("laptop").excluding("refurbished"))
+//!     .category_ids(&["177"])
+//!     .access_token(access_token)
+//!     .build()?;
+//! # Ok::<(), ebay_api_test::error::EbayError>(())
+//! ```
+
+use std::fmt;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Keyword query rendered into eBay's `q` parameter
+pub struct Query {
+    parts: Vec<String>,
+}
+
+impl Query {
+    /// Plain keywords, passed to eBay as written
+    pub fn new(keywords: &str) -> Self {
+        Query::from_part(keywords.trim().to_string())
+    }
+
+    /// Exact phrase, wrapped in quotes
+    pub fn phrase(text: &str) -> Self {
+        Query::from_part(quote(text))
+    }
+
+    /// Every term must appear in the listing
+    pub fn all_of(terms: &[&str]) -> Self {
+        let rendered: Vec<String> = terms
+            .iter()
+            .map(|term| term_or_phrase(term))
+            .filter(|term| !term.is_empty())
+            .collect();
+
+        Query::from_part(rendered.join(" "))
+    }
+
+    /// At least one of the terms must appear in the listing
+    pub fn any_of(terms: &[&str]) -> Self {
+        let rendered: Vec<String> = terms
+            .iter()
+            .map(|term| term_or_phrase(term))
+            .filter(|term| !term.is_empty())
+            .collect();
+
+        match rendered.len() {
+            0 => Query::default(),
+            1 => Query::from_part(rendered[0].clone()),
+            _ => Query::from_part(format!("({})", rendered.join(","))),
+        }
+    }
+
+    /// Listings containing the term are left out of the results
+    pub fn exclude(term: &str) -> Self {
+        let rendered = term_or_phrase(term);
+
+        if rendered.is_empty() {
+            return Query::default();
+        }
+
+        Query::from_part(format!("-{}", rendered))
+    }
+
+    /// Leave out listings containing the term, shorthand for `.and(Query::exclude(term))`
+    pub fn excluding(self, term: &str) -> Self {
+        self.and(Query::exclude(term))
+    }
+
+    /// Combine two queries, both of which must match
+    pub fn and(mut self, other: Query) -> Self {
+        self.parts.extend(other.parts);
+        self
+    }
+
+    /// True when no terms have been added
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// The rendered value of the `q` parameter
+    pub fn as_q(&self) -> String {
+        self.parts.join(" ")
+    }
+
+    fn from_part(part: String) -> Self {
+        if part.is_empty() {
+            return Query::default();
+        }
+
+        Query { parts: vec![part] }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_q())
+    }
+}
+
+impl From<Query> for Value {
+    fn from(query: Query) -> Self {
+        Value::String(query.as_q())
+    }
+}
+
+// Quote the term if it contains whitespace so eBay treats it as a phrase
+fn term_or_phrase(term: &str) -> String {
+    let term = term.trim();
+
+    if term.contains(char::is_whitespace) {
+        quote(term)
+    } else {
+        term.replace('"', "")
+    }
+}
+
+fn quote(text: &str) -> String {
+    let cleaned = text.trim().replace('"', "");
+
+    if cleaned.is_empty() {
+        return cleaned;
+    }
+
+    format!("\"{}\"", cleaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ebay_api::SearchConfig;
+
+    #[test]
+    fn terms_are_combined_with_the_keyword_grammar() {
+        let query = Query::new("laptop")
+            .and(Query::any_of(&["thinkpad", "latitude"]))
+            .and(Query::phrase("new york"))
+            .excluding("refurbished");
+
+        assert_eq!(query.as_q(), "laptop (thinkpad,latitude) \"new york\" -refurbished");
+    }
+
+    #[test]
+    fn terms_with_whitespace_become_phrases() {
+        assert_eq!(Query::all_of(&["black", "leather jacket"]).as_q(), "black \"leather jacket\"");
+        assert_eq!(Query::any_of(&["usb c", "lightning"]).as_q(), "(\"usb c\",lightning)");
+        assert_eq!(Query::exclude("for parts").as_q(), "-\"for parts\"");
+        assert_eq!(Query::phrase("say \"hi\"").as_q(), "\"say hi\"");
+    }
+
+    #[test]
+    fn empty_terms_are_dropped() {
+        assert!(Query::new("  ").is_empty());
+        assert!(Query::phrase("\"\"").is_empty());
+        assert!(Query::exclude(" ").is_empty());
+        assert!(Query::any_of(&[]).is_empty());
+        assert_eq!(Query::any_of(&["", "ipad"]).as_q(), "ipad");
+        assert_eq!(Query::all_of(&["", "ipad", " "]).as_q(), "ipad");
+        assert_eq!(Query::new("laptop").excluding("").as_q(), "laptop");
+    }
+
+    #[test]
+    fn exclusions_are_sent_alongside_categories() {
+        let config = SearchConfig::builder()
+            .query(Query::new("laptop").excluding("refurbished"))
+            .category_ids(&["177"])
+            .access_token("token")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.search_parameters["q"], "laptop -refurbished");
+        assert_eq!(config.search_parameters["category_ids"], "177");
+    }
+}
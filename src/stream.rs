@@ -0,0 +1,125 @@
+//! # Stream Module
+//!
+//! The `stream` module provides `SearchStream`, which walks through every result of a search
+//! one item at a time, fetching the next page only when the current one is used up.
+//!
+//! ## Resuming
+//!
+//! `offset()` is the position of the next item the stream will yield. Store it as a checkpoint
+//! and pass it to `EbayClient::search_stream_from` to pick up at the same item later, e.g.
+//! after a crash, instead of fetching everything again.
+//!
+//! ## Example Usage
+//!
+//! ```no_run
+//! use ebay_api_test::client::EbayClient;
+//! use ebay_api_test::ebay_api::SearchConfig;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), ebay_api_test::error::EbayError> {
+//!     let client = EbayClient::new();
+//!     let config = SearchConfig::builder()
+//!         .query("laptop")
+//!         .access_token("Your-OAuth-Access-Token")
+//!         .build()?;
+//!
+//!     // Offset saved by an earlier run, 0 on the first one
+//!     let checkpoint: u64 = std::fs::read_to_string("checkpoint")
+//!         .ok()
+//!         .and_then(|saved| saved.trim().parse().ok())
+//!         .unwrap_or(0);
+//!
+//!     let mut stream = client.search_stream_from(config, checkpoint);
+//!     while let Some(item) = stream.next().await {
+//!         let item = item?;
+//!         println!("{}", item.title);
+//!         std::fs::write("checkpoint", stream.offset().to_string())?;
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use serde_json::json;
+
+use crate::client::{ numeric_param, EbayClient, MAX_RESULT_WINDOW };
+use crate::ebay_api::SearchConfig;
+use crate::error::EbayError;
+use crate::models::ItemSummary;
+
+#[derive(Debug)]
+/// Item by item iterator over all pages of a search
+pub struct SearchStream<'a> {
+    client: &'a EbayClient,
+    config: SearchConfig,
+    buffer: VecDeque<ItemSummary>,
+    offset: u64,
+    next_page_offset: u64,
+    finished: bool,
+}
+
+impl<'a> SearchStream<'a> {
+    /// Yield the next item, fetching another page when needed.
+    /// Returns None once eBay has no more results; after an error, calling
+    /// `next` again retries the page that failed.
+    pub async fn next(&mut self) -> Option<Result<ItemSummary, EbayError>> {
+        if self.buffer.is_empty() && !self.finished {
+            if let Err(err) = self.fetch_page().await {
+                return Some(Err(err));
+            }
+        }
+
+        let item = self.buffer.pop_front()?;
+        self.offset += 1;
+
+        Some(Ok(item))
+    }
+
+    /// Offset of the next item `next` will yield, to resume from later
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    async fn fetch_page(&mut self) -> Result<(), EbayError> {
+        if self.next_page_offset >= MAX_RESULT_WINDOW {
+            self.finished = true;
+            return Ok(());
+        }
+
+        self.config.search_parameters.insert(String::from("offset"), json!(self.next_page_offset));
+
+        let response = self.client.search_page(&mut self.config, 0).await?;
+        let page_len = response.item_summaries.len() as u64;
+
+        self.next_page_offset += page_len;
+        self.finished = page_len == 0 ||
+            self.next_page_offset >= response.total ||
+            response.next.is_none();
+        self.buffer.extend(response.item_summaries);
+
+        Ok(())
+    }
+}
+
+impl EbayClient {
+    /// Stream every result of the search, starting at the config's `offset`
+    pub fn search_stream(&self, config: SearchConfig) -> SearchStream<'_> {
+        let offset = numeric_param(&config, "offset").unwrap_or(0);
+
+        self.search_stream_from(config, offset)
+    }
+
+    /// Stream every result of the search, starting at the given offset, typically one
+    /// saved from `SearchStream::offset`
+    pub fn search_stream_from(&self, config: SearchConfig, offset: u64) -> SearchStream<'_> {
+        SearchStream {
+            client: self,
+            config,
+            buffer: VecDeque::new(),
+            offset,
+            next_page_offset: offset,
+            finished: false,
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! # Telemetry Module
+//!
+//! The `telemetry` module records request health through the `metrics` facade when the
+//! `metrics` feature is enabled, and compiles to nothing otherwise. The application picks
+//! the exporter (Prometheus, StatsD, ...) by installing a `metrics` recorder.
+//!
+//! ## Metrics
+//!
+//! - `ebay_requests_total` (counter): every request sent to eBay.
+//! - `ebay_request_errors_total` (counter, label `status`): failed requests, by status code,
+//!   or `transport` when no response arrived.
+//! - `ebay_retries_total` (counter, label `reason`): requests retried after a failure.
+//! - `ebay_request_duration_seconds` (histogram): round trip time of each request.
+
+use std::time::Duration;
+
+/// Record a finished request. `status` is None when no response arrived.
+pub fn record_request(status: Option<u16>, duration: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!("ebay_requests_total").increment(1);
+        metrics::histogram!("ebay_request_duration_seconds").record(duration.as_secs_f64());
+
+        match status {
+            Some(status) if status < 400 => {}
+            Some(status) => {
+                metrics::counter!("ebay_request_errors_total", "status" => status.to_string()).increment(1);
+            }
+            None => {
+                metrics::counter!("ebay_request_errors_total", "status" => "transport").increment(1);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = (status, duration);
+}
+
+/// Record that a request is being retried, e.g. `reason` = `expired_token`, `transient` or `connection`
+pub fn record_retry(reason: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("ebay_retries_total", "reason" => reason).increment(1);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = reason;
+}
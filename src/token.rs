@@ -0,0 +1,222 @@
+//! # Token Module
+//!
+//! The `token` module provides `TokenManager`, which obtains OAuth application access tokens
+//! from eBay using the client credentials grant and keeps the most recent one cached.
+//!
+//! ## Example Usage
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use ebay_api_test::ebay_api::{SearchConfig, run_search};
+//! use ebay_api_test::token::TokenManager;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let manager = Arc::new(TokenManager::new("Your-App-ID", "Your-Cert-ID"));
+//!     let access_token = manager.access_token().await.unwrap();
+//!
+//!     // If the token expires while the program is running, run_search refreshes it
+//!     // through the manager and retries the request once
+//!     let config = SearchConfig::new(serde_json::json!("laptop"), access_token)
+//!         .token_manager(manager);
+//!     let results = run_search(config).await;
+//! }
+//! ```
+
+use std::fmt;
+use std::time::{ Duration, Instant };
+use serde_derive::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::ebay_api::{ Environment, TOKEN_PATH };
+use crate::error::EbayError;
+
+const PUBLIC_SCOPE: &str = "https://api.ebay.com/oauth/api_scope";
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+struct CachedToken {
+    access_token: Secret,
+    expires_at: Instant,
+}
+
+/// Fetches and caches application access tokens for an eBay developer keyset. Its Debug
+/// output leaves out the keyset and the cached token.
+pub struct TokenManager {
+    app_id: String,
+    cert_id: Secret,
+    token_url: String,
+    scope: String,
+    client: reqwest::Client,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+impl fmt::Debug for TokenManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenManager")
+            .field("app_id", &redacted(&self.app_id))
+            .field("cert_id", &redacted(expose(&self.cert_id)))
+            .field("token_url", &self.token_url)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TokenManager {
+    /// Create a new Token Manager
+    /// app_id -> eBay developer application ID (client id)
+    /// cert_id -> eBay developer certificate ID (client secret)
+    pub fn new(app_id: &str, cert_id: &str) -> Self {
+        TokenManager {
+            app_id: app_id.to_string(),
+            cert_id: secret(cert_id),
+            token_url: Environment::Sandbox.token_url(),
+            scope: String::from(PUBLIC_SCOPE),
+            client: reqwest::Client::new(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Request tokens from the given environment instead of the sandbox.
+    /// Sandbox and production keysets are not interchangeable.
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.token_url = environment.token_url();
+        self
+    }
+
+    /// Request tokens from another server, such as a local fixture server,
+    /// e.g. `base_url("http://localhost:8080")`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.token_url = format!("{}{}", base_url.trim_end_matches('/'), TOKEN_PATH);
+        self
+    }
+
+    /// eBay developer application ID the tokens are requested for
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    // The keyset's secret, for configs built from a client with this manager
+    pub(crate) fn cert_id(&self) -> &str {
+        expose(&self.cert_id)
+    }
+
+    /// Return the cached token, fetching a new one if there is none or it has expired
+    pub async fn access_token(&self) -> Result<String, EbayError> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(token) = cached.as_ref() {
+            if Instant::now() < token.expires_at {
+                return Ok(expose(&token.access_token).to_string());
+            }
+        }
+
+        let token = self.fetch().await?;
+        let access_token = expose(&token.access_token).to_string();
+        *cached = Some(token);
+
+        Ok(access_token)
+    }
+
+    /// Discard the cached token and fetch a new one from eBay
+    pub async fn refresh(&self) -> Result<String, EbayError> {
+        let mut cached = self.cached.lock().await;
+
+        let token = self.fetch().await?;
+        let access_token = expose(&token.access_token).to_string();
+        *cached = Some(token);
+
+        Ok(access_token)
+    }
+
+    /// Replace a token eBay rejected as expired. When another request already refreshed it
+    /// in the meantime, the newer cached token is returned instead of fetching yet another,
+    /// so requests failing together on the same expired token share a single refresh.
+    pub async fn refresh_rejected(&self, rejected: &str) -> Result<String, EbayError> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(token) = cached.as_ref() {
+            let replaced = expose(&token.access_token) != rejected;
+            if replaced && Instant::now() < token.expires_at {
+                return Ok(expose(&token.access_token).to_string());
+            }
+        }
+
+        let token = self.fetch().await?;
+        let access_token = expose(&token.access_token).to_string();
+        *cached = Some(token);
+
+        Ok(access_token)
+    }
+
+    async fn fetch(&self) -> Result<CachedToken, EbayError> {
+        let response = self.client
+            .post(&self.token_url)
+            .basic_auth(&self.app_id, Some(expose(&self.cert_id)))
+            .form(
+                &[
+                    ("grant_type", "client_credentials"),
+                    ("scope", self.scope.as_str()),
+                ]
+            )
+            .send().await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(EbayError::Token(format!("token endpoint returned {}: {}", status, body)));
+        }
+
+        let token: TokenResponse = serde_json::from_str(&body)?;
+
+        // Treat the token as expired a minute early so it never lapses mid-request
+        let lifetime = Duration::from_secs(token.expires_in.saturating_sub(60));
+
+        Ok(CachedToken {
+            access_token: secret(token.access_token),
+            expires_at: Instant::now() + lifetime,
+        })
+    }
+}
+
+#[cfg(feature = "secrecy")]
+/// Credential held in memory: a `secrecy::SecretString` with the `secrecy` feature, which
+/// prints as `[REDACTED]` in Debug output, and a plain `String` without it
+pub type Secret = secrecy::SecretString;
+
+#[cfg(not(feature = "secrecy"))]
+/// Credential held in memory: a `secrecy::SecretString` with the `secrecy` feature, which
+/// prints as `[REDACTED]` in Debug output, and a plain `String` without it
+pub type Secret = String;
+
+/// Wrap a credential for storage
+pub fn secret(value: impl Into<String>) -> Secret {
+    #[cfg(feature = "secrecy")]
+    return secrecy::SecretString::from(value.into());
+
+    #[cfg(not(feature = "secrecy"))]
+    return value.into();
+}
+
+/// What Debug output shows in place of a credential, so a set one never appears in logs
+pub(crate) fn redacted(value: &str) -> &'static str {
+    if value.is_empty() {
+        ""
+    } else {
+        "[REDACTED]"
+    }
+}
+
+/// Read a stored credential, only done where it goes into a request
+pub fn expose(secret: &Secret) -> &str {
+    #[cfg(feature = "secrecy")]
+    return secrecy::ExposeSecret::expose_secret(secret);
+
+    #[cfg(not(feature = "secrecy"))]
+    return secret.as_str();
+}
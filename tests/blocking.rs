@@ -0,0 +1,82 @@
+//! The blocking client against the fixture server, called from plain synchronous tests
+
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{ MockServer, Reply, Request };
+use ebay_api_test::blocking::BlockingClient;
+use ebay_api_test::ebay_api::{ SearchConfig, ITEM_PATH };
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+const ACCESS_TOKEN: &str = "fixture-token";
+
+// The fixture server runs on a runtime of its own, off the test's thread, so the blocking
+// client never finds itself inside one
+fn server<F>(handler: F) -> (Runtime, MockServer)
+where
+    F: Fn(&Request) -> Option<Reply> + Send + Sync + 'static,
+{
+    let runtime = Runtime::new().unwrap();
+    let server = runtime.block_on(MockServer::start(handler));
+
+    (runtime, server)
+}
+
+#[test]
+fn search_all_collects_every_page() {
+    let (_runtime, server) = server(|request| {
+        let offset: u64 = request.query_param("offset").and_then(|offset| offset.parse().ok()).unwrap_or(0);
+        let end = (offset + 2).min(5);
+        let items: Vec<_> = (offset..end).map(|index| json!({ "itemId": format!("v1|{}|0", index) })).collect();
+        let next = (end < 5).then(|| format!("/next?offset={}", end));
+
+        let page = json!({ "total": 5, "limit": 2, "offset": offset, "next": next, "itemSummaries": items });
+        Some(Reply::json(200, page))
+    });
+    let config = SearchConfig::builder().query("laptop").limit(2).access_token(ACCESS_TOKEN).build().unwrap();
+
+    let items = BlockingClient::with_base_url(&server.base_url).search_all(config, 100).unwrap();
+
+    let ids: Vec<String> = items.into_iter().map(|item| item.item_id).collect();
+    assert_eq!(ids, (0..5).map(|index| format!("v1|{}|0", index)).collect::<Vec<_>>());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn get_items_sends_batches_of_twenty() {
+    let (_runtime, server) = server(|request| {
+        // Query values arrive percent-encoded, `v1%7C1%7C0%2Cv1%7C2%7C0`
+        let ids = request.query_param("item_ids").unwrap_or_default().replace("%7C", "|");
+        let items: Vec<_> = ids.split("%2C").map(|id| json!({ "itemId": id })).collect();
+
+        Some(Reply::json(200, json!({ "items": items })))
+    });
+    let ids: Vec<String> = (0..45).map(|index| format!("v1|{}|0", index)).collect();
+
+    let items = BlockingClient::with_base_url(&server.base_url).get_items(&ids, ACCESS_TOKEN).unwrap();
+
+    let fetched: Vec<String> = items.into_iter().map(|item| item.item_id).collect();
+    assert_eq!(fetched, ids);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    for request in &requests {
+        assert_eq!(request.path(), format!("{}/", ITEM_PATH));
+        assert_eq!(request.header("authorization"), Some("Bearer fixture-token"));
+    }
+}
+
+#[test]
+fn error_status_is_an_error() {
+    let (_runtime, server) = server(|_| {
+        let errors = json!({ "errors": [{ "errorId": 1001, "domain": "OAuth", "message": "Invalid access token" }] });
+        Some(Reply::json(401, errors))
+    });
+
+    let err = BlockingClient::with_base_url(&server.base_url)
+        .get_items(&[String::from("v1|1|0")], ACCESS_TOKEN)
+        .unwrap_err();
+
+    assert!(err.is_expired_token(), "{:?}", err);
+}
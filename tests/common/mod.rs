@@ -0,0 +1,181 @@
+//! Fixture HTTP server shared by the integration tests. Every connection carries one request,
+//! answered by the test's handler and closed, and every request is recorded so tests can
+//! check what went out on the wire.
+
+#![allow(dead_code)]
+
+use std::sync::{ Arc, Mutex };
+use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+use tokio::net::{ TcpListener, TcpStream };
+
+#[derive(Debug, Clone)]
+/// A request as the fixture server received it
+pub struct Request {
+    pub method: String,
+    /// Path and query string, e.g. `/buy/browse/v1/item_summary/search?limit=5&q=laptop`
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    /// Path without the query string
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    /// Raw, still percent-encoded value of a query parameter
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.target
+            .split_once('?')?
+            .1
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+    }
+
+    /// Every value of the header, matched ignoring case
+    pub fn header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    /// First value of the header
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.header_values(name).into_iter().next()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Response the handler sends back
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    /// A JSON response
+    pub fn json(status: u16, body: impl ToString) -> Self {
+        Reply {
+            status,
+            headers: vec![(String::from("Content-Type"), String::from("application/json"))],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// Add a header, e.g. `Content-Encoding`
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Replace the body with raw bytes, e.g. a gzip stream
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+/// Handler deciding the reply to each request; None drops the connection without answering
+pub type Handler = dyn Fn(&Request) -> Option<Reply> + Send + Sync;
+
+/// Local fixture server standing in for eBay
+pub struct MockServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Start listening on a free local port
+    pub async fn start(handler: impl Fn(&Request) -> Option<Reply> + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("failed to bind fixture server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+
+                tokio::spawn(async move {
+                    let _ = answer(socket, handler.as_ref(), &recorded).await;
+                });
+            }
+        });
+
+        MockServer { base_url, requests }
+    }
+
+    /// Every request received so far, in the order they arrived
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn answer(
+    mut socket: TcpStream,
+    handler: &Handler,
+    recorded: &Mutex<Vec<Request>>
+) -> std::io::Result<()> {
+    let mut data = Vec::new();
+    let mut buffer = [0u8; 4096];
+
+    let head_end = loop {
+        if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        let read = socket.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        data.extend_from_slice(&buffer[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let request = Request {
+        method: request_line.next().unwrap_or_default().to_string(),
+        target: request_line.next().unwrap_or_default().to_string(),
+        headers: lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect(),
+    };
+
+    // Read the body too, so closing the socket doesn't reset the connection under the client
+    let content_length: usize = request
+        .header("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    while data.len() < head_end + 4 + content_length {
+        let read = socket.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&buffer[..read]);
+    }
+
+    recorded.lock().unwrap().push(request.clone());
+
+    let Some(reply) = handler(&request) else {
+        return Ok(());
+    };
+
+    let mut response = format!("HTTP/1.1 {} Fixture\r\n", reply.status);
+    for (name, value) in &reply.headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", reply.body.len()));
+
+    socket.write_all(response.as_bytes()).await?;
+    socket.write_all(&reply.body).await?;
+    socket.shutdown().await
+}
@@ -0,0 +1,76 @@
+//! Searches with response compression turned on and off
+
+mod common;
+
+use std::io::Write;
+use common::{ MockServer, Reply };
+use ebay_api_test::client::EbayClient;
+use ebay_api_test::ebay_api::SearchConfig;
+use ebay_api_test::error::EbayError;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::json;
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+// Sends the page gzip compressed whenever the request accepts it, like eBay does
+async fn gzip_server() -> MockServer {
+    MockServer::start(|request| {
+        let page = json!({
+            "total": 2,
+            "limit": 5,
+            "offset": 0,
+            "itemSummaries": [
+                { "itemId": "v1|1|0", "title": "ThinkPad X1", "price": { "value": "499.99", "currency": "USD" } },
+                { "itemId": "v1|2|0", "title": "Latitude 7440", "price": { "value": "649.00", "currency": "USD" } },
+            ],
+        });
+        let reply = Reply::json(200, &page);
+
+        let accepts_gzip = request
+            .header("accept-encoding")
+            .is_some_and(|encoding| encoding.contains("gzip"));
+        if !accepts_gzip {
+            return Some(reply);
+        }
+
+        Some(reply.header("Content-Encoding", "gzip").body(gzip(page.to_string().as_bytes())))
+    }).await
+}
+
+fn config() -> SearchConfig {
+    SearchConfig::builder().query("laptop").access_token("fixture-token").build().unwrap()
+}
+
+#[tokio::test]
+async fn parsed_results_are_the_same_with_and_without_compression() {
+    let server = gzip_server().await;
+    let compressed = EbayClient::with_base_url(&server.base_url).compression(true).unwrap();
+    let plain = EbayClient::with_base_url(&server.base_url).compression(false).unwrap();
+
+    let compressed = compressed.search(config()).await.unwrap();
+    let plain = plain.search(config()).await.unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&compressed).unwrap(),
+        serde_json::to_value(&plain).unwrap()
+    );
+    assert_eq!(compressed.item_summaries.len(), 2);
+
+    let requests = server.requests();
+    assert!(requests[0].header("accept-encoding").unwrap().contains("gzip"));
+    assert_eq!(requests[1].header("accept-encoding"), None);
+}
+
+#[test]
+fn compression_leaves_a_shared_http_client_alone() {
+    let client = EbayClient::with_http_client(reqwest::Client::new());
+
+    let err = client.compression(false).unwrap_err();
+
+    assert!(matches!(err, EbayError::Config(_)), "{:?}", err);
+}
@@ -0,0 +1,82 @@
+//! Bulk ingestion from an in-memory ID list to in-memory NDJSON, against the fixture server
+
+mod common;
+
+use common::{ MockServer, Reply };
+use ebay_api_test::client::EbayClient;
+use serde_json::{ json, Value };
+
+const ACCESS_TOKEN: &str = "fixture-token";
+
+// Answers each batch with its items in request order, leaving out the ID eBay has no listing for
+async fn item_server(missing: &'static str) -> MockServer {
+    MockServer::start(move |request| {
+        // Query values arrive percent-encoded, `v1%7C1%7C0%2Cv1%7C2%7C0`
+        let ids = request.query_param("item_ids")?.replace("%7C", "|");
+        let items: Vec<_> = ids
+            .split("%2C")
+            .filter(|id| *id != missing)
+            .map(|id| json!({ "itemId": id, "title": "Laptop" }))
+            .collect();
+
+        Some(Reply::json(200, json!({ "items": items })))
+    }).await
+}
+
+#[tokio::test]
+async fn ingest_ids_writes_every_batch_as_ndjson_in_input_order() {
+    let server = item_server("v1|7|0").await;
+    let ids: Vec<String> = (0..45).map(|index| format!("v1|{}|0", index)).collect();
+
+    // Every input form the reader accepts, with a blank line in between
+    let mut input = String::new();
+    for (index, id) in ids.iter().enumerate() {
+        match index % 3 {
+            0 => input.push_str(&format!("{}\n", id)),
+            1 => input.push_str(&format!("\"{}\"\n", id)),
+            _ => input.push_str(&format!("{{\"itemId\": \"{}\"}}\n\n", id)),
+        }
+    }
+    let mut output = Vec::new();
+
+    let summary = EbayClient::with_base_url(&server.base_url)
+        .ingest_ids(input.as_bytes(), &mut output, ACCESS_TOKEN, 2).await
+        .unwrap();
+
+    assert_eq!(summary.ids_read, 45);
+    assert_eq!(summary.items_written, 44);
+
+    let batch_sizes: Vec<usize> = server
+        .requests()
+        .iter()
+        .map(|request| request.query_param("item_ids").unwrap().split("%2C").count())
+        .collect();
+    assert_eq!(batch_sizes.len(), 3);
+    assert_eq!(batch_sizes.iter().sum::<usize>(), 45);
+    assert!(batch_sizes.iter().all(|size| *size <= 20), "{:?}", batch_sizes);
+
+    let written: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let item: Value = serde_json::from_str(line).unwrap();
+            item["itemId"].as_str().unwrap().to_string()
+        })
+        .collect();
+    let expected: Vec<String> = ids.into_iter().filter(|id| id != "v1|7|0").collect();
+    assert_eq!(written, expected);
+}
+
+#[tokio::test]
+async fn ingest_ids_stops_at_a_malformed_line() {
+    let server = item_server("").await;
+    let input = "v1|1|0\n{\"itemId\": \n";
+    let mut output = Vec::new();
+
+    let result = EbayClient::with_base_url(&server.base_url)
+        .ingest_ids(input.as_bytes(), &mut output, ACCESS_TOKEN, 1).await;
+
+    assert!(result.is_err());
+    assert!(server.requests().is_empty());
+    assert!(output.is_empty());
+}
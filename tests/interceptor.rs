@@ -0,0 +1,43 @@
+//! Request interceptors against the fixture server
+
+mod common;
+
+use std::sync::Arc;
+use common::{ MockServer, Reply };
+use ebay_api_test::client::EbayClient;
+use ebay_api_test::ebay_api::SearchConfig;
+use ebay_api_test::interceptor::RequestInterceptor;
+use serde_json::json;
+
+const ACCESS_TOKEN: &str = "fixture-token";
+
+#[derive(Debug)]
+struct Signer;
+
+impl RequestInterceptor for Signer {
+    fn intercept(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request.header("X-Gateway-Signature", "signed")
+    }
+}
+
+#[tokio::test]
+async fn interceptor_headers_reach_the_server() {
+    let server = MockServer::start(|request| {
+        let body = match request.path().ends_with("/search") {
+            true => json!({ "total": 0, "itemSummaries": [] }),
+            false => json!({ "itemId": "v1|1|0", "title": "Laptop" }),
+        };
+        Some(Reply::json(200, body))
+    }).await;
+    let client = EbayClient::with_base_url(&server.base_url).interceptor(Arc::new(Signer));
+    let config = SearchConfig::builder().query("laptop").access_token(ACCESS_TOKEN).build().unwrap();
+
+    client.search(config).await.unwrap();
+    client.get_item("v1|1|0", ACCESS_TOKEN).await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        assert_eq!(request.header_values("x-gateway-signature"), ["signed"], "{}", request.target);
+    }
+}
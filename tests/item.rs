@@ -0,0 +1,142 @@
+//! Item lookups against the fixture server
+
+mod common;
+
+use std::sync::Arc;
+use common::{ MockServer, Reply };
+use ebay_api_test::client::EbayClient;
+use ebay_api_test::ebay_api::TOKEN_PATH;
+use ebay_api_test::item::ItemLookup;
+use ebay_api_test::token::TokenManager;
+use serde_json::json;
+
+const EXPIRED_TOKEN: &str = "expired-token";
+const FRESH_TOKEN: &str = "fresh-token";
+
+// Rejects every item request not made with the fresh token, as eBay does once a token lapses
+async fn expiring_server() -> MockServer {
+    MockServer::start(|request| {
+        if request.path() == TOKEN_PATH {
+            return Some(Reply::json(200, json!({ "access_token": FRESH_TOKEN, "expires_in": 7200 })));
+        }
+
+        if request.header("authorization") != Some(&format!("Bearer {}", FRESH_TOKEN)) {
+            let errors = json!({
+                "errors": [{ "errorId": 1001, "domain": "OAuth", "message": "Invalid access token" }]
+            });
+            return Some(Reply::json(401, errors));
+        }
+
+        let item = |id: &str| json!({ "itemId": id, "title": "Laptop" });
+        // Query values arrive percent-encoded, `v1%7C1%7C0%2Cv1%7C2%7C0`
+        let body = match request.query_param("item_ids") {
+            Some(ids) => json!({ "items": ids.replace("%7C", "|").split("%2C").map(item).collect::<Vec<_>>() }),
+            None => item(request.path().rsplit('/').next().unwrap_or_default()),
+        };
+        Some(Reply::json(200, body))
+    }).await
+}
+
+fn client(server: &MockServer) -> EbayClient {
+    let mut client = EbayClient::with_base_url(&server.base_url);
+    let manager = TokenManager::new("app-id", "cert-id").base_url(&server.base_url);
+    client.token_manager = Some(Arc::new(manager));
+    client
+}
+
+#[tokio::test]
+async fn get_item_refreshes_an_expired_token_once() {
+    let server = expiring_server().await;
+
+    let item = client(&server).get_item("v1|110551991234|0", EXPIRED_TOKEN).await.unwrap();
+
+    assert_eq!(item.item_id, "v1|110551991234|0");
+    let paths: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|request| request.path().to_string())
+        .collect();
+    assert_eq!(paths.len(), 3, "item, token, item again: {:?}", paths);
+    assert_eq!(paths[1], TOKEN_PATH);
+}
+
+#[tokio::test]
+async fn get_items_refreshes_an_expired_token() {
+    let server = expiring_server().await;
+    let ids = vec![String::from("v1|1|0"), String::from("v1|2|0")];
+
+    let items = client(&server).get_items(&ids, EXPIRED_TOKEN).await.unwrap();
+
+    let ids: Vec<&str> = items.iter().map(|item| item.item_id.as_str()).collect();
+    assert_eq!(ids, ["v1|1|0", "v1|2|0"]);
+}
+
+#[tokio::test]
+async fn expired_token_without_a_token_manager_is_an_error() {
+    let server = expiring_server().await;
+    let client = EbayClient::with_base_url(&server.base_url);
+
+    let err = client.get_item("v1|110551991234|0", EXPIRED_TOKEN).await.unwrap_err();
+
+    assert!(err.is_expired_token(), "{:?}", err);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn concurrent_expired_requests_share_one_refresh() {
+    let server = expiring_server().await;
+    // Three batches of 20, sent concurrently and all rejected with the expired token
+    let ids: Vec<String> = (0..60).map(|index| format!("v1|{}|0", index)).collect();
+
+    let items = client(&server).get_items(&ids, EXPIRED_TOKEN).await.unwrap();
+
+    assert_eq!(items.len(), 60);
+    let refreshes = server
+        .requests()
+        .iter()
+        .filter(|request| request.path() == TOKEN_PATH)
+        .count();
+    assert_eq!(refreshes, 1);
+}
+
+#[tokio::test]
+async fn numeric_legacy_ids_and_group_ids_go_to_their_own_endpoints() {
+    let server = MockServer::start(|request| {
+        let item = |id: &str| json!({ "itemId": id, "title": "Laptop" });
+        let body = match request.path().rsplit('/').next().unwrap_or_default() {
+            "get_item_by_legacy_id" => item(&format!("v1|{}|0", request.query_param("legacy_item_id")?)),
+            "get_items_by_item_group" => {
+                let group_id = request.query_param("item_group_id")?;
+                json!({ "items": [item(&format!("v1|{}|1", group_id)), item(&format!("v1|{}|2", group_id))] })
+            }
+            _ => json!({ "items": [item(&request.query_param("item_ids")?.replace("%7C", "|"))] }),
+        };
+        Some(Reply::json(200, body))
+    }).await;
+    let ids = [
+        ItemLookup::Group(String::from("151845160231")),
+        ItemLookup::Legacy(String::from("110551991234")),
+        ItemLookup::Item(String::from("v1|110551995678|0")),
+    ];
+
+    let items = EbayClient::with_base_url(&server.base_url)
+        .get_items_and_groups(&ids, FRESH_TOKEN).await
+        .unwrap();
+
+    let ids: Vec<&str> = items.iter().map(|item| item.item_id.as_str()).collect();
+    assert_eq!(ids, ["v1|110551995678|0", "v1|110551991234|0", "v1|151845160231|1", "v1|151845160231|2"]);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn item_lookups_request_json() {
+    let server = expiring_server().await;
+    let client = client(&server);
+
+    client.get_item("v1|1|0", FRESH_TOKEN).await.unwrap();
+    client.get_items(&[String::from("v1|2|0")], FRESH_TOKEN).await.unwrap();
+
+    for request in server.requests() {
+        assert_eq!(request.header_values("accept"), ["application/json"], "{}", request.target);
+    }
+}
@@ -0,0 +1,114 @@
+//! A three page search, followed by `search_all`, by `fetch_next` and by a resumed stream
+
+mod common;
+
+use common::{ MockServer, Reply };
+use ebay_api_test::client::EbayClient;
+use ebay_api_test::ebay_api::{ SearchConfig, SEARCH_PATH };
+use serde_json::json;
+
+// Matches across the three pages: two full pages and a short last one
+const TOTAL: u64 = 10;
+const PAGE_SIZE: u64 = 4;
+
+const ACCESS_TOKEN: &str = "fixture-token";
+
+fn item_id(index: u64) -> String {
+    format!("v1|{}|0", 110551991000 + index)
+}
+
+// Answers every request with the page for its offset, linking to the next one
+async fn server() -> MockServer {
+    MockServer::start(|request| {
+        let base_url = format!("http://{}", request.header("host").unwrap_or_default());
+        let offset = request.query_param("offset").and_then(|offset| offset.parse().ok()).unwrap_or(0);
+
+        let end = (offset + PAGE_SIZE).min(TOTAL);
+        let items: Vec<_> = (offset..end)
+            .map(|index| json!({ "itemId": item_id(index), "title": format!("Laptop {}", index) }))
+            .collect();
+        let link = |offset: u64| {
+            format!("{}{}?q=laptop&limit={}&offset={}", base_url, SEARCH_PATH, PAGE_SIZE, offset)
+        };
+
+        Some(Reply::json(200, json!({
+            "href": link(offset),
+            "total": TOTAL,
+            "limit": PAGE_SIZE,
+            "offset": offset,
+            "next": if end < TOTAL { Some(link(end)) } else { None },
+            "itemSummaries": items,
+        })))
+    }).await
+}
+
+fn config() -> SearchConfig {
+    SearchConfig::builder()
+        .query("laptop")
+        .limit(PAGE_SIZE as u32)
+        .access_token(ACCESS_TOKEN)
+        .build()
+        .unwrap()
+}
+
+fn expected() -> Vec<String> {
+    (0..TOTAL).map(item_id).collect()
+}
+
+#[tokio::test]
+async fn search_all_collects_every_page_once_in_order() {
+    let server = server().await;
+
+    // Asking for more than there are, so only the last page can stop it
+    let items = EbayClient::with_base_url(&server.base_url).search_all(config(), 100).await.unwrap();
+
+    let ids: Vec<String> = items.into_iter().map(|item| item.item_id).collect();
+    assert_eq!(ids, expected());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn fetch_next_follows_the_links_to_the_last_page() {
+    let server = server().await;
+    let client = EbayClient::with_base_url(&server.base_url);
+
+    let mut page = client.search(config()).await.unwrap();
+    let mut ids: Vec<String> = page.item_summaries.iter().map(|item| item.item_id.clone()).collect();
+    while let Some(next) = client.fetch_next(&page, ACCESS_TOKEN).await.unwrap() {
+        ids.extend(next.item_summaries.iter().map(|item| item.item_id.clone()));
+        page = next;
+    }
+
+    assert_eq!(ids, expected());
+    let requests = server.requests();
+    let offsets: Vec<Option<&str>> = requests.iter().map(|request| request.query_param("offset")).collect();
+    assert_eq!(offsets[1..], [Some("4"), Some("8")]);
+    assert_eq!(requests.len(), 3);
+}
+
+#[tokio::test]
+async fn stream_resumed_from_its_offset_yields_every_item_once_in_order() {
+    let server = server().await;
+    let client = EbayClient::with_base_url(&server.base_url);
+
+    // Stop partway through the second page, as a crash would
+    let mut ids = Vec::new();
+    let mut stream = client.search_stream(config());
+    for _ in 0..6 {
+        ids.push(stream.next().await.unwrap().unwrap().item_id);
+    }
+    let checkpoint = stream.offset();
+    assert_eq!(checkpoint, 6);
+
+    let mut resumed = client.search_stream_from(config(), checkpoint);
+    while let Some(item) = resumed.next().await {
+        ids.push(item.unwrap().item_id);
+    }
+
+    assert_eq!(ids, expected());
+    assert_eq!(resumed.offset(), TOTAL);
+    let requests = server.requests();
+    let offsets: Vec<Option<&str>> = requests.iter().map(|request| request.query_param("offset")).collect();
+    // The resumed stream starts at the checkpoint and stops after the last page
+    assert_eq!(offsets, [Some("0"), Some("4"), Some("6")]);
+}
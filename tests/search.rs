@@ -0,0 +1,190 @@
+//! Single searches against the fixture server, checking what goes out on the wire
+
+mod common;
+
+use std::time::Duration;
+use common::{ MockServer, Reply };
+use ebay_api_test::client::EbayClient;
+use ebay_api_test::ebay_api::{ self, SearchConfig, SEARCH_PATH };
+use ebay_api_test::error::EbayError;
+use ebay_api_test::filter::{ Condition, SearchFilter };
+use ebay_api_test::models::SearchResponse;
+use ebay_api_test::token::secret;
+use reqwest::header::{ HeaderMap, HeaderValue, AUTHORIZATION };
+use serde_json::json;
+
+const ACCESS_TOKEN: &str = "fixture-token";
+
+async fn server() -> MockServer {
+    MockServer::start(|_| Some(Reply::json(200, json!({ "total": 0, "itemSummaries": [] })))).await
+}
+
+fn config() -> SearchConfig {
+    SearchConfig::builder().query("laptop").access_token(ACCESS_TOKEN).build().unwrap()
+}
+
+#[tokio::test]
+async fn exactly_one_authorization_header_is_sent() {
+    let server = server().await;
+    let mut extra = HeaderMap::new();
+    extra.insert(AUTHORIZATION, HeaderValue::from_static("Bearer from-caller"));
+    let mut config = config().with_headers(extra);
+    // Even a second value added by hand is dropped before sending
+    config.headers.append(AUTHORIZATION, HeaderValue::from_static("Bearer appended"));
+
+    EbayClient::with_base_url(&server.base_url).search(config).await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.header_values("authorization"), [format!("Bearer {}", ACCESS_TOKEN)]);
+}
+
+#[tokio::test]
+async fn errors_sent_with_a_200_are_an_error() {
+    let server = MockServer::start(|_| {
+        let errors = json!({
+            "errors": [{ "errorId": 12001, "domain": "API_BROWSE", "message": "The 'filter' value is invalid." }]
+        });
+        Some(Reply::json(200, errors))
+    }).await;
+
+    let err = EbayClient::with_base_url(&server.base_url).search(config()).await.unwrap_err();
+
+    match err {
+        EbayError::Api { status, errors, .. } => {
+            assert_eq!(status, 200);
+            assert_eq!(errors[0].error_id, 12001);
+        }
+        other => panic!("expected an Api error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn post_query_aliases_forward_to_run_search() {
+    let server = MockServer::start(|_| {
+        let items = json!([{ "itemId": "v1|1|0", "title": "Laptop" }]);
+        Some(Reply::json(200, json!({ "total": 1, "itemSummaries": items })))
+    }).await;
+    let config = SearchConfig::builder()
+        .query("laptop")
+        .access_token(ACCESS_TOKEN)
+        .search_url(&format!("{}{}", server.base_url, SEARCH_PATH))
+        .build()
+        .unwrap();
+
+    let expected = ebay_api::run_search(config.clone()).await.unwrap();
+    let response = ebay_api::post_query(config.clone()).await.unwrap();
+    let (raw_response, raw) = ebay_api::post_query_raw(config.clone()).await.unwrap();
+    let as_value: serde_json::Value = ebay_api::post_query_as(config).await.unwrap();
+
+    let expected = serde_json::to_value(&expected).unwrap();
+    assert_eq!(serde_json::to_value(&response).unwrap(), expected);
+    assert_eq!(serde_json::to_value(&raw_response).unwrap(), expected);
+    assert_eq!(serde_json::from_slice::<SearchResponse>(&raw).unwrap().total, 1);
+    assert_eq!(as_value["itemSummaries"][0]["itemId"], "v1|1|0");
+
+    // Every alias sent the same request as run_search
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    for request in &requests[1..] {
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path(), requests[0].path());
+        assert_eq!(request.query_param("q"), requests[0].query_param("q"));
+    }
+}
+
+#[tokio::test]
+async fn limit_is_sent_as_a_bare_number() {
+    let server = server().await;
+    let client = EbayClient::with_base_url(&server.base_url);
+    let config = SearchConfig::builder().query("laptop").limit(5).access_token(ACCESS_TOKEN).build().unwrap();
+
+    client.search(config.clone()).await.unwrap();
+    // A limit set as a string by hand goes out the same way
+    client.search(config.extra_param("limit", "5")).await.unwrap();
+
+    for request in server.requests() {
+        assert_eq!(request.query_param("limit"), Some("5"), "{}", request.target);
+    }
+}
+
+#[tokio::test]
+async fn json_is_requested_explicitly() {
+    let server = server().await;
+
+    EbayClient::with_base_url(&server.base_url).search(config()).await.unwrap();
+
+    assert_eq!(server.requests()[0].header_values("accept"), ["application/json"]);
+}
+
+#[tokio::test]
+async fn unread_response_keeps_its_request_permit() {
+    let server = server().await;
+    let client = EbayClient::with_base_url(&server.base_url).max_concurrent_requests(1);
+
+    let first = client.send_search(&config()).await.unwrap();
+    let blocked = tokio::time::timeout(Duration::from_millis(100), client.search(config())).await;
+    assert!(blocked.is_err(), "a second search ran while the first body was unread");
+
+    first.bytes().await.unwrap();
+    client.search(config()).await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn default_limit_applies_unless_the_config_sets_one() {
+    let server = server().await;
+    let mut client = EbayClient::with_base_url(&server.base_url);
+    client.access_token = Some(secret(ACCESS_TOKEN));
+
+    client.search_query("laptop").await.unwrap();
+    let client = client.default_limit(20).unwrap();
+    client.search_query("laptop").await.unwrap();
+    let config = client.search_config().await.unwrap().query("laptop").limit(3).build().unwrap();
+    client.search(config).await.unwrap();
+
+    let requests = server.requests();
+    let limits: Vec<Option<&str>> = requests.iter().map(|request| request.query_param("limit")).collect();
+    assert_eq!(limits, [Some("50"), Some("20"), Some("3")]);
+}
+
+#[tokio::test]
+async fn count_reads_the_total_from_a_single_result() {
+    let server = MockServer::start(|_| Some(Reply::json(200, json!({ "total": 1234, "itemSummaries": [] })))).await;
+    let filter = SearchFilter::new().conditions(&[Condition::New]);
+
+    let total = EbayClient::with_base_url(&server.base_url).count("laptop", filter, ACCESS_TOKEN).await.unwrap();
+
+    assert_eq!(total, 1234);
+    let request = &server.requests()[0];
+    assert_eq!(request.query_param("limit"), Some("1"));
+    assert_eq!(request.query_param("filter"), Some("conditionIds%3A%7B1000%7D"));
+}
+
+#[tokio::test]
+async fn config_without_a_limit_sends_the_client_default() {
+    let server = server().await;
+    let client = EbayClient::with_base_url(&server.base_url);
+
+    // Neither sets a limit, whichever way the config is made
+    client.search(config()).await.unwrap();
+    client.search(SearchConfig::new(json!("laptop"), String::from(ACCESS_TOKEN))).await.unwrap();
+    client.search_all(config(), 10).await.unwrap();
+    let mut larger = EbayClient::builder().default_limit(120).unwrap();
+    larger.base_url = Some(server.base_url.clone());
+    larger.search(config()).await.unwrap();
+
+    let requests = server.requests();
+    let limits: Vec<Option<&str>> = requests.iter().map(|request| request.query_param("limit")).collect();
+    assert_eq!(limits, [Some("50"), Some("50"), Some("50"), Some("120")]);
+}
+
+#[test]
+fn default_limit_must_be_a_valid_page_size() {
+    for limit in [0, 201] {
+        let err = EbayClient::new().default_limit(limit).unwrap_err();
+        assert!(matches!(err, EbayError::Validation(_)), "{:?}", err);
+    }
+
+    assert_eq!(EbayClient::builder().default_limit(200).unwrap().default_limit, 200);
+}
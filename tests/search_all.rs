@@ -0,0 +1,124 @@
+//! Paginated searches against misbehaving fixture servers
+
+mod common;
+
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::Duration;
+use common::{ MockServer, Reply };
+use ebay_api_test::client::{ EbayClient, RetryConfig };
+use ebay_api_test::ebay_api::{ SearchConfig, SEARCH_PATH };
+use ebay_api_test::error::EbayError;
+use serde_json::json;
+
+// A page with one item that always claims there is more to come
+fn endless_page(base_url: &str, index: usize) -> Reply {
+    Reply::json(
+        200,
+        json!({
+            "total": 1000,
+            "limit": 5,
+            "offset": index,
+            "next": format!("{}{}?offset={}", base_url, SEARCH_PATH, index + 1),
+            "itemSummaries": [{ "itemId": format!("v1|{}|0", index), "title": "Laptop" }],
+        })
+    )
+}
+
+fn config(server: &MockServer) -> SearchConfig {
+    SearchConfig::builder()
+        .query("laptop")
+        .limit(5)
+        .access_token("fixture-token")
+        .search_url(&format!("{}{}", server.base_url, SEARCH_PATH))
+        .build()
+        .unwrap()
+}
+
+fn client(server: &MockServer) -> EbayClient {
+    EbayClient::with_base_url(&server.base_url).with_retry(RetryConfig {
+        base_delay: Duration::from_millis(1),
+        ..RetryConfig::default()
+    })
+}
+
+#[tokio::test]
+async fn flapping_server_exhausts_the_request_budget() {
+    let requests = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| {
+        let index = requests.fetch_add(1, Ordering::SeqCst);
+        if index.is_multiple_of(2) {
+            return Some(Reply::json(503, json!({ "errors": [] })));
+        }
+        let base_url = format!("http://{}", request.header("host").unwrap_or_default());
+        Some(endless_page(&base_url, index))
+    }).await;
+
+    // 10 items at 5 per page need 2 pages, plus the default margin of 5 requests
+    let err = client(&server).search_all(config(&server), 10).await.unwrap_err();
+
+    match err {
+        EbayError::Pagination(message) => assert!(message.starts_with("sent 7 requests"), "{}", message),
+        other => panic!("expected a Pagination error, got {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 7);
+}
+
+#[tokio::test]
+async fn consecutive_errors_stop_the_search() {
+    let server = MockServer::start(|_| Some(Reply::json(503, json!({ "errors": [] })))).await;
+
+    let err = client(&server).search_all(config(&server), 10).await.unwrap_err();
+
+    match err {
+        EbayError::Pagination(message) => {
+            assert!(message.starts_with("3 consecutive errors at offset 0"), "{}", message)
+        }
+        other => panic!("expected a Pagination error, got {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn request_margin_is_configurable() {
+    let server = MockServer::start(|request| {
+        let base_url = format!("http://{}", request.header("host").unwrap_or_default());
+        Some(endless_page(&base_url, 0))
+    }).await;
+    let client = EbayClient::with_base_url(&server.base_url).with_retry(RetryConfig {
+        request_margin: 0,
+        ..RetryConfig::default()
+    });
+
+    // Pages of one item never fill 10 items within the 2 requests they should need
+    let err = client.search_all(config(&server), 10).await.unwrap_err();
+
+    assert!(matches!(err, EbayError::Pagination(_)), "{:?}", err);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn dropped_connection_is_retried() {
+    let requests = AtomicUsize::new(0);
+    let server = MockServer::start(move |_| {
+        // Accept the first connection and close it without answering
+        if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+            return None;
+        }
+        let items = json!([{ "itemId": "v1|1|0", "title": "Laptop" }]);
+        Some(Reply::json(200, json!({ "total": 1, "limit": 5, "offset": 0, "itemSummaries": items })))
+    }).await;
+
+    let items = client(&server).search_all(config(&server), 10).await.unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn dropped_connection_is_a_connection_error() {
+    let server = MockServer::start(|_| None).await;
+
+    let err = client(&server).search(config(&server)).await.unwrap_err();
+
+    assert!(err.is_connection_error(), "{:?}", err);
+}